@@ -1,14 +1,46 @@
-use core::ops::Deref;
+use core::ops::{Deref, Range};
+use core::sync::atomic::{AtomicU64, Ordering};
 
-mod interp;
+use crate::diagnostic::SourceId;
+
+mod check;
+mod compile;
 mod parser;
 
+pub(crate) use check::Exports;
+
+/// A byte range in the source a node was parsed from
+pub type Span = Range<usize>;
+
+/// A node, along with the span of source it was parsed from
+#[derive(Clone, Debug, PartialEq)]
+pub struct Spanned<T> {
+    pub node: T,
+    pub span: Span,
+}
+
+impl<T> Spanned<T> {
+    pub fn new(node: T, span: Span) -> Spanned<T> {
+        Spanned { node, span }
+    }
+}
+
+impl<T> Deref for Spanned<T> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        &self.node
+    }
+}
+
 #[derive(Clone, Debug)]
 pub struct Assign {
     ty: AssignTy,
     ident: Ident,
+    /// The fields being updated, for `record.field = value`
+    fields: Vec<Ident>,
     assign_op: AssignOp,
-    val: Expr,
+    val: Spanned<Expr>,
 }
 
 #[derive(Copy, Clone, Debug)]
@@ -31,46 +63,116 @@ pub enum AssignTy {
 pub enum BinOp {
     Eq,
     Neq,
+    Lt,
+    Gt,
+    LtEq,
+    GtEq,
     Add,
     Sub,
     Mul,
     Div,
+    BitAnd,
+    BitOr,
+    BitXor,
+    And,
+    Or,
 }
 
 #[derive(Clone, Debug)]
 pub enum Expr {
     FnCall(FnCall),
-    Write(WriteTy, Vec<Expr>),
+    Write(WriteTy, Vec<Spanned<Expr>>),
+    /// `read(source)`, `read(source, count)` or `read(source, all)`
+    Read(ReadTy, ReadAmount),
     Literal(Literal),
     Ident(Ident),
-    UnOp(UnOp, Box<Expr>),
-    BinOp(Box<Expr>, BinOp, Box<Expr>),
+    UnOp(UnOp, Box<Spanned<Expr>>),
+    BinOp(Box<Spanned<Expr>>, BinOp, Box<Spanned<Expr>>),
+    /// `value.field`
+    Field(Box<Spanned<Expr>>, Ident),
+    /// `value#index`
+    Index(Box<Spanned<Expr>>, Box<Spanned<Expr>>),
+    /// `Name { field: value, ... }`
+    Record(Ident, Vec<(Ident, Spanned<Expr>)>),
+    /// `callee(args, ...)`, where the callee is anything but a plain name, like `module.fn(args)`
+    Call(Box<Spanned<Expr>>, Vec<Spanned<Expr>>),
 }
 
 #[derive(Debug)]
 pub struct File {
-    stmts: Vec<Stmt>,
+    source: SourceId,
+    imports: Vec<Spanned<Import>>,
+    stmts: Vec<Spanned<Stmt>>,
+}
+
+impl File {
+    pub fn source(&self) -> SourceId {
+        self.source
+    }
+
+    pub(crate) fn set_source(&mut self, source: SourceId) {
+        self.source = source;
+    }
+
+    /// The imports at the top of the file, which are loaded before any of its statements run
+    pub fn imports(&self) -> &[Spanned<Import>] {
+        &self.imports
+    }
+
+    pub fn stmts(&self) -> &[Spanned<Stmt>] {
+        &self.stmts
+    }
+}
+
+/// `import "path" as name;`, or `import { a, b as c } from "path";`
+#[derive(Clone, Debug)]
+pub struct Import {
+    path: Spanned<String>,
+    binding: ImportBinding,
+}
+
+impl Import {
+    /// The path of the imported file, as written
+    pub fn path(&self) -> &Spanned<String> {
+        &self.path
+    }
+
+    pub fn binding(&self) -> &ImportBinding {
+        &self.binding
+    }
+}
+
+#[derive(Clone, Debug)]
+pub enum ImportBinding {
+    /// Bind the whole module under one name, so its definitions are reached as `name.def`
+    Namespace(Ident),
+    /// Bind some of the module's definitions directly, each under a name and the definition it
+    /// stands for
+    Select(Vec<(Ident, Spanned<Ident>)>),
 }
 
 #[derive(Clone, Debug)]
 pub struct FnArg {
     name: Ident,
-    ty: Type,
+    ty: Spanned<Type>,
 }
 
 #[derive(Clone, Debug)]
 pub struct FnCall {
     name: Ident,
-    args: Vec<Expr>,
+    args: Vec<Spanned<Expr>>,
 }
 
 #[derive(Clone, Debug)]
 pub struct FnDef {
     name: Ident,
-    ret: Type,
+    /// Type parameters, bound to the types of the arguments at each call
+    generics: Vec<Ident>,
+    ret: Spanned<Type>,
     args: Vec<FnArg>,
-    limit: Box<Expr>,
-    stmts: Vec<Stmt>,
+    limit: Box<Spanned<Expr>>,
+    stmts: Vec<Spanned<Stmt>>,
+    span: Span,
 }
 
 impl FnDef {
@@ -79,11 +181,15 @@ impl FnDef {
     }
 
     pub fn ret_ty(&self) -> &Type {
-        &self.ret
+        &self.ret.node
     }
 
     pub fn arg_tys(&self) -> Vec<Type> {
-        self.args.iter().map(|a| a.ty.clone()).collect()
+        self.args.iter().map(|a| a.ty.node.clone()).collect()
+    }
+
+    pub fn span(&self) -> Span {
+        self.span.clone()
     }
 }
 
@@ -101,22 +207,23 @@ impl Deref for Ident {
 #[derive(Clone, Debug)]
 pub enum Literal {
     Int(i128),
+    BigInt(num_bigint::BigInt),
     Float(f64),
     Char(char),
     CharArray(String),
     Bit(bool),
     Fn(FnDef),
-    Array(Vec<Expr>),
+    Array(Vec<Spanned<Expr>>),
 }
 
 #[derive(Clone, Debug)]
 pub enum Stmt {
     FnDef(FnDef),
     Assign(Assign),
-    Once(Vec<Stmt>),
-    Sync(Vec<Stmt>),
-    Expr(Expr),
-    TypeDef(Ident, Type),
+    Once(Vec<Spanned<Stmt>>),
+    Sync(Vec<Spanned<Stmt>>),
+    Expr(Spanned<Expr>),
+    TypeDef(Ident, Spanned<Type>),
 }
 
 #[derive(Clone, Debug, PartialEq)]
@@ -124,13 +231,112 @@ pub enum Type {
     Named(Ident),
     Array(Box<Type>),
     Fn(Box<Type>, Vec<Type>),
+    /// A record declared with `type Name = { field: type, ... }`. Records are only equal to the
+    /// declaration they came from, so two declarations with the same fields differ, even if they
+    /// share a name too.
+    Record(Decl, Vec<(Ident, Type)>),
+    /// A tagged union declared with `type Name = Variant(type) | Other | ...`, where each variant
+    /// may carry a payload. Like records, unions are only equal to the declaration they came from.
+    Union(Decl, Vec<(Ident, Option<Type>)>),
+}
+
+/// The name of a record or union, along with which declaration it came from
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Decl {
+    name: Ident,
+    id: u64,
+}
+
+impl Decl {
+    /// A declaration distinct from every other made while the program runs
+    pub(crate) fn new(name: Ident) -> Decl {
+        static NEXT_ID: AtomicU64 = AtomicU64::new(0);
+        Decl {
+            name,
+            id: NEXT_ID.fetch_add(1, Ordering::Relaxed),
+        }
+    }
+
+    pub fn name(&self) -> &Ident {
+        &self.name
+    }
 }
 
 impl Type {
-    pub(crate) fn named(name: &str) -> Type {
+    pub fn named(name: &str) -> Type {
         Type::Named(Ident(name.to_string()))
     }
 
+    /// Whether a value of type `ty` can be used where this type is expected. `any` stands for
+    /// every type, so builtins like `map` can accept arrays of anything.
+    pub fn matches(&self, ty: &Type) -> bool {
+        match (self, ty) {
+            (Type::Named(name), _) if &**name == "any" => true,
+            (Type::Array(_), Type::Array(inner)) if inner.is_empty_elem() => true,
+            (Type::Array(expected), Type::Array(inner)) => expected.matches(inner),
+            (Type::Fn(expected_ret, expected_args), Type::Fn(ret, args)) => {
+                expected_ret.matches(ret)
+                    && expected_args.len() == args.len()
+                    && expected_args.iter().zip(args).all(|(expected, arg)| expected.matches(arg))
+            }
+            _ => self == ty,
+        }
+    }
+
+    /// Whether values of the two types can be used together. An empty array's elements have type
+    /// `any`, so it agrees with every array type.
+    pub fn agrees(&self, other: &Type) -> bool {
+        match (self, other) {
+            (Type::Array(a), Type::Array(b)) => {
+                a.is_empty_elem() || b.is_empty_elem() || a.agrees(b)
+            }
+            _ => self == other,
+        }
+    }
+
+    /// The more specific of two types that agree, filling in what an empty array leaves open, or
+    /// `None` if they don't agree
+    pub fn join(&self, other: &Type) -> Option<Type> {
+        match (self, other) {
+            (Type::Array(a), Type::Array(_)) if a.is_empty_elem() => Some(other.clone()),
+            (Type::Array(_), Type::Array(b)) if b.is_empty_elem() => Some(self.clone()),
+            (Type::Array(a), Type::Array(b)) => Some(Type::Array(Box::new(a.join(b)?))),
+            _ => (self == other).then(|| self.clone()),
+        }
+    }
+
+    /// Whether this is the element type of an empty array
+    fn is_empty_elem(&self) -> bool {
+        *self == Type::named("any")
+    }
+
+    /// Whether `any` appears anywhere in this type
+    pub fn is_generic(&self) -> bool {
+        match self {
+            Type::Named(name) => &**name == "any",
+            Type::Array(inner) => inner.is_generic(),
+            Type::Fn(ret, args) => ret.is_generic() || args.iter().any(Type::is_generic),
+            Type::Record(_, _) | Type::Union(_, _) => false,
+        }
+    }
+
+    /// Replace type parameters with the types they're bound to, leaving unbound ones as they are
+    pub fn substitute(&self, generics: &[(Ident, Option<Type>)]) -> Type {
+        match self {
+            Type::Named(name) => generics
+                .iter()
+                .find_map(|(param, bound)| bound.as_ref().filter(|_| param == name))
+                .cloned()
+                .unwrap_or_else(|| self.clone()),
+            Type::Array(inner) => Type::Array(Box::new(inner.substitute(generics))),
+            Type::Fn(ret, args) => Type::Fn(
+                Box::new(ret.substitute(generics)),
+                args.iter().map(|arg| arg.substitute(generics)).collect(),
+            ),
+            Type::Record(_, _) | Type::Union(_, _) => self.clone(),
+        }
+    }
+
     pub fn pretty(&self) -> String {
         match self {
             Type::Named(name) => String::from(&**name),
@@ -140,9 +346,10 @@ impl Type {
                 ret.pretty(),
                 args.iter()
                     .map(|ty| ty.pretty())
-                    .intersperse(String::from(" "))
-                    .collect::<String>(),
+                    .collect::<Vec<_>>()
+                    .join(" "),
             ),
+            Type::Record(decl, _) | Type::Union(decl, _) => String::from(&**decl.name()),
         }
     }
 }
@@ -158,5 +365,22 @@ pub enum WriteTy {
     Console,
     Error,
     RawFile,
-    Other(Box<Expr>),
+    Other(Box<Spanned<Expr>>),
+}
+
+#[derive(Clone, Debug)]
+pub enum ReadTy {
+    Console,
+    Other(Box<Spanned<Expr>>),
+}
+
+/// How much a `read` takes from its source
+#[derive(Clone, Debug)]
+pub enum ReadAmount {
+    /// Up to and including the next newline
+    Line,
+    /// Everything until the end of input
+    All,
+    /// Up to a number of chars
+    Count(Box<Spanned<Expr>>),
 }
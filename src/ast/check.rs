@@ -0,0 +1,617 @@
+use std::collections::{HashMap, HashSet};
+use std::rc::Rc;
+
+use super::*;
+use crate::diagnostic::{Diagnostic, Location, SourceId};
+use crate::interp::{Env, ExceptionKind, Fn, Op};
+
+/// What's known about a variable before the program runs
+#[derive(Clone)]
+struct Var {
+    /// The variable's type, if it could be inferred
+    ty: Option<Type>,
+    defined: Option<Location>,
+    /// Whether the variable is a builtin accepting any arguments
+    variadic: bool,
+    /// What the module bound to the variable defines, along with the path it was imported by
+    module: Option<(String, Rc<Exports>)>,
+}
+
+/// What the checker knows of the definitions a file leaves at its top level, which become its
+/// module when it's imported
+#[derive(Clone, Default)]
+pub(crate) struct Exports(HashMap<String, Var>);
+
+struct Checker<'a, 'ip> {
+    source: SourceId,
+    env: &'a Env<'ip>,
+    scopes: Vec<HashMap<String, Var>>,
+    /// The names each enclosing block defines somewhere, including after the point being checked
+    declared: Vec<HashSet<String>>,
+    diagnostics: Vec<Diagnostic>,
+    /// Types bound to names with `type`, already resolved, for each scope
+    aliases: Vec<HashMap<String, Type>>,
+}
+
+impl Checker<'_, '_> {
+    fn location(&self, span: &Span) -> Location {
+        Location::new(self.source, span.clone())
+    }
+
+    fn error(&mut self, span: &Span, kind: ExceptionKind) {
+        let diag = Diagnostic::new("Type Error", kind.to_string())
+            .with_location(Some(self.location(span)));
+        self.diagnostics.push(diag);
+    }
+
+    /// Attach a label to the last error reported
+    fn label(&mut self, span: &Span, message: String) {
+        self.label_at(self.location(span), message);
+    }
+
+    /// Attach a label to the last error reported, pointing into any source
+    fn label_at(&mut self, location: Location, message: String) {
+        if let Some(diag) = self.diagnostics.pop() {
+            self.diagnostics.push(diag.with_label(location, message));
+        }
+    }
+
+    /// Look a variable up, falling back to what's already defined in the environment
+    fn lookup(&self, name: &str) -> Option<Var> {
+        self.scopes
+            .iter()
+            .rev()
+            .find_map(|scope| scope.get(name).cloned())
+            .or_else(|| {
+                self.env.lookup_var(name).map(|val| Var {
+                    ty: Some(val.ty()),
+                    defined: None,
+                    variadic: val.downcast::<Fn<'_>>().is_ok_and(Fn::is_variadic),
+                    module: None,
+                })
+            })
+    }
+
+    /// Look up a name the program uses, reporting it if it can't be defined by the time it's used.
+    /// A function body runs when it's called, and again on each iteration, so inside one a name
+    /// defined later on in an enclosing block may well exist. Its type isn't known yet though.
+    fn resolve_name(&mut self, name: &Ident, span: &Span) -> Option<Var> {
+        let var = self.lookup(name);
+        let in_fn = self.scopes.len() > 1;
+        if var.is_none() && !(in_fn && self.declared.iter().any(|names| names.contains(&**name))) {
+            self.error(span, ExceptionKind::NameNotFound(name.clone()));
+        }
+        var
+    }
+
+    /// Look up a definition `inner.name` reads from an imported module, reporting it if the module
+    /// doesn't have one. Gives `None` when `inner` isn't a module the checker knows about.
+    fn imported(&mut self, inner: &Spanned<Expr>, name: &Ident, span: &Span) -> Option<Var> {
+        let (path, exports) = match &inner.node {
+            Expr::Ident(ident) => self.lookup(ident)?.module?,
+            _ => return None,
+        };
+        Some(exports.0.get(&**name).cloned().unwrap_or_else(|| {
+            self.error(span, ExceptionKind::NotExported(path, name.clone()));
+            Var { ty: None, defined: None, variadic: false, module: None }
+        }))
+    }
+
+    /// Replace aliases with the types they stand for, including those defined before checking
+    fn resolve(&self, ty: &Type) -> Type {
+        match ty {
+            Type::Named(name) => match self.aliases.iter().rev().find_map(|scope| scope.get(&**name)) {
+                Some(alias) => alias.clone(),
+                None => ty.resolve(self.env),
+            },
+            Type::Array(inner) => Type::Array(Box::new(self.resolve(inner))),
+            Type::Fn(ret, args) => Type::Fn(
+                Box::new(self.resolve(ret)),
+                args.iter().map(|arg| self.resolve(arg)).collect(),
+            ),
+            Type::Record(_, _) | Type::Union(_, _) => ty.clone(),
+        }
+    }
+
+    fn define(&mut self, name: &str, ty: Option<Type>, defined: Span) {
+        let var = Var {
+            ty,
+            defined: Some(self.location(&defined)),
+            variadic: false,
+            module: None,
+        };
+        self.scopes.last_mut().unwrap().insert(name.to_string(), var);
+    }
+
+    fn op(&mut self, span: &Span, op: Op, operands: &[Option<Type>]) -> Option<Type> {
+        let operands = operands.iter().cloned().collect::<Option<Vec<_>>>()?;
+        let out = op_result(op, &operands);
+        if out.is_none() {
+            self.error(
+                span,
+                ExceptionKind::InvalidOp(op, operands[0].clone(), operands.get(1).cloned()),
+            );
+        }
+        out
+    }
+
+    fn expr(&mut self, expr: &Spanned<Expr>) -> Option<Type> {
+        match &expr.node {
+            Expr::FnCall(call) => self.call(call, &expr.span),
+            Expr::Call(callee, args) => self.call_expr(callee, args, &expr.span),
+            Expr::Write(ty, args) => {
+                if let WriteTy::Other(target) = ty {
+                    self.expr(target);
+                }
+                for arg in args {
+                    self.expr(arg);
+                }
+                Some(Type::named("null"))
+            }
+            Expr::Read(ty, amount) => {
+                if let ReadTy::Other(source) = ty {
+                    self.expr(source);
+                }
+                if let ReadAmount::Count(count) = amount {
+                    if let Some(count_ty) = self.expr(count) {
+                        if count_ty != Type::named("int") {
+                            self.error(&count.span, ExceptionKind::InvalidType(Type::named("int"), count_ty));
+                        }
+                    }
+                }
+                Some(Type::named("chararray"))
+            }
+            Expr::Literal(lit) => self.literal(lit),
+            Expr::Ident(name) => self.resolve_name(name, &expr.span).and_then(|var| var.ty),
+            &Expr::UnOp(op, ref inner) => {
+                let ty = self.expr(inner);
+                self.op(&expr.span, op.into(), &[ty])
+            }
+            &Expr::BinOp(ref left, op, ref right) => {
+                let left = self.expr(left);
+                let right = self.expr(right);
+                self.op(&expr.span, op.into(), &[left, right])
+            }
+            Expr::Field(inner, name) => {
+                if let Some(def) = self.imported(inner, name, &expr.span) {
+                    return def.ty;
+                }
+                let ty = self.expr(inner)?;
+                let field = field_ty(&ty, name);
+                if field.is_none() {
+                    self.error(&expr.span, ExceptionKind::FieldNotFound(ty, name.clone()));
+                }
+                field.map(|field| self.resolve(&field))
+            }
+            Expr::Index(inner, idx) => {
+                let ty = self.expr(inner);
+                if let Some(idx_ty) = self.expr(idx) {
+                    if idx_ty != Type::named("int") {
+                        self.error(&idx.span, ExceptionKind::InvalidType(Type::named("int"), idx_ty));
+                    }
+                }
+                match ty? {
+                    Type::Array(elem) => Some(*elem),
+                    ty if ty == Type::named("chararray") => Some(Type::named("char")),
+                    ty => {
+                        self.error(&expr.span, ExceptionKind::NotIndexable(ty));
+                        None
+                    }
+                }
+            }
+            Expr::Record(name, fields) => self.record(name, fields, &expr.span),
+        }
+    }
+
+    fn record(&mut self, name: &Ident, fields: &[(Ident, Spanned<Expr>)], span: &Span) -> Option<Type> {
+        let vals = fields.iter().map(|(_, val)| self.expr(val)).collect::<Vec<_>>();
+
+        let ty = match self.resolve_name(name, span)?.ty? {
+            ty if ty == Type::named("type") => self.resolve(&Type::Named(name.clone())),
+            ty => {
+                self.error(span, ExceptionKind::InvalidType(Type::named("type"), ty));
+                return None;
+            }
+        };
+        let decl = match &ty {
+            Type::Record(_, decl) => decl,
+            _ => {
+                self.error(span, ExceptionKind::NotARecord(ty));
+                return None;
+            }
+        };
+
+        for ((field, expr), val) in fields.iter().zip(&vals) {
+            match decl.iter().find(|(name, _)| name == field) {
+                Some((_, expected)) => {
+                    let expected = self.resolve(expected);
+                    if let Some(val) = val.as_ref().filter(|val| !val.agrees(&expected)) {
+                        self.error(&expr.span, ExceptionKind::InvalidType(expected, val.clone()));
+                    }
+                }
+                None => self.error(span, ExceptionKind::FieldNotFound(ty.clone(), field.clone())),
+            }
+        }
+        for (name, _) in decl {
+            if fields.iter().all(|(field, _)| field != name) {
+                self.error(span, ExceptionKind::MissingField(ty.clone(), name.clone()));
+            }
+        }
+        Some(ty)
+    }
+
+    fn call(&mut self, call: &FnCall, span: &Span) -> Option<Type> {
+        let args = call.args.iter().map(|arg| self.expr(arg)).collect::<Vec<_>>();
+        let callee = self.resolve_name(&call.name, span)?;
+        self.call_with(&call.name, callee, &call.args, args, span)
+    }
+
+    /// Check a call to a callee other than a plain name, which is only known by its type
+    fn call_expr(&mut self, callee: &Spanned<Expr>, exprs: &[Spanned<Expr>], span: &Span) -> Option<Type> {
+        let (name, def) = match &callee.node {
+            Expr::Field(inner, name) => (name.clone(), self.imported(inner, name, &callee.span)),
+            _ => (Ident(String::from("<fn>")), None),
+        };
+        let callee = def.unwrap_or_else(|| Var {
+            ty: self.expr(callee),
+            defined: None,
+            variadic: false,
+            module: None,
+        });
+        let args = exprs.iter().map(|arg| self.expr(arg)).collect::<Vec<_>>();
+        self.call_with(&name, callee, exprs, args, span)
+    }
+
+    fn call_with(
+        &mut self,
+        name: &Ident,
+        callee: Var,
+        exprs: &[Spanned<Expr>],
+        args: Vec<Option<Type>>,
+        span: &Span,
+    ) -> Option<Type> {
+        let (ret, params) = match callee.ty? {
+            Type::Fn(ret, params) => (*ret, params),
+            ty => {
+                self.error(span, ExceptionKind::InvalidType(Type::Fn(Box::new(Type::named("any")), vec![]), ty));
+                return None;
+            }
+        };
+        // Builtins taking `any` check their own arguments, and may return anything
+        let ret = Some(ret).filter(|ret| !ret.is_generic());
+        if callee.variadic {
+            return ret;
+        }
+
+        if params.len() != args.len() {
+            self.error(span, ExceptionKind::InvalidArgCount(name.clone(), params.len(), args.len()));
+            if let Some(defined) = callee.defined {
+                self.label_at(defined, format!("`{}` defined here", &**name));
+            }
+            return ret;
+        }
+
+        for ((param, arg), expr) in params.iter().zip(&args).zip(exprs) {
+            if let Some(arg) = arg {
+                if !param.matches(arg) {
+                    self.error(&expr.span, ExceptionKind::InvalidType(param.clone(), arg.clone()));
+                }
+            }
+        }
+        ret
+    }
+
+    fn literal(&mut self, lit: &Literal) -> Option<Type> {
+        let ty = match lit {
+            Literal::Int(_) => Type::named("int"),
+            Literal::BigInt(_) => Type::named("bigint"),
+            Literal::Float(_) => Type::named("float"),
+            Literal::Char(_) => Type::named("char"),
+            Literal::CharArray(_) => Type::named("chararray"),
+            Literal::Bit(_) => Type::named("bit"),
+            Literal::Fn(def) => {
+                self.fn_def(def);
+                self.resolve(&def.ty())
+            }
+            Literal::Array(elems) => {
+                let tys = elems.iter().map(|elem| self.expr(elem)).collect::<Vec<_>>();
+                let mut joined = match tys.first() {
+                    Some(first) => first.clone()?,
+                    None => Type::named("any"),
+                };
+                // Each element has to agree with the most specific type of the ones before it
+                for (ty, elem) in tys.iter().zip(elems).skip(1) {
+                    if let Some(ty) = ty {
+                        match joined.join(ty) {
+                            Some(ty) => joined = ty,
+                            None => {
+                                self.error(&elem.span, ExceptionKind::InvalidType(joined.clone(), ty.clone()))
+                            }
+                        }
+                    }
+                }
+                Type::Array(Box::new(joined))
+            }
+        };
+        Some(ty)
+    }
+
+    /// Check a statement, returning the type of the value it leaves for the limit check
+    fn stmt(&mut self, stmt: &Spanned<Stmt>) -> Option<Type> {
+        match &stmt.node {
+            Stmt::FnDef(def) => {
+                let ty = self.resolve(&def.ty());
+                self.define(&def.name, Some(ty), def.span.clone());
+                self.fn_def(def);
+                Some(Type::named("null"))
+            }
+            Stmt::Assign(assign) => self.assign(assign, &stmt.span),
+            Stmt::Once(stmts) | Stmt::Sync(stmts) => {
+                for stmt in stmts {
+                    self.stmt(stmt);
+                }
+                Some(Type::named("null"))
+            }
+            Stmt::Expr(expr) => self.expr(expr),
+            Stmt::TypeDef(name, ty) => {
+                let ty = self.resolve(ty);
+                if let Type::Union(_, variants) = &ty {
+                    for (variant, payload) in variants {
+                        let constructor = Type::Fn(Box::new(ty.clone()), payload.iter().cloned().collect());
+                        self.define(variant, Some(self.resolve(&constructor)), stmt.span.clone());
+                    }
+                }
+                self.aliases.last_mut().unwrap().insert(name.to_string(), ty);
+                self.define(name, Some(Type::named("type")), stmt.span.clone());
+                Some(Type::named("null"))
+            }
+        }
+    }
+
+    fn assign(&mut self, assign: &Assign, span: &Span) -> Option<Type> {
+        if !assign.fields.is_empty() {
+            return self.assign_fields(assign, span);
+        }
+
+        let val = self.expr(&assign.val);
+        let op = match assign.assign_op {
+            AssignOp::Eq => None,
+            AssignOp::PlusEq => Some(Op::Add),
+            AssignOp::SubEq => Some(Op::Sub),
+            AssignOp::MulEq => Some(Op::Mul),
+            AssignOp::DivEq => Some(Op::Div),
+        };
+
+        if let Some(op) = op {
+            let var = self.resolve_name(&assign.ident, span).and_then(|var| var.ty);
+            let ty = self.op(span, op, &[var, val]);
+            self.define(&assign.ident, ty.clone(), span.clone());
+            return ty;
+        }
+
+        match assign.ty {
+            AssignTy::Unique => self.define(&assign.ident, val.clone(), span.clone()),
+            // Later iterations leave null instead
+            AssignTy::CarryOver => {
+                self.define(&assign.ident, val, span.clone());
+                return None;
+            }
+            AssignTy::Default => {
+                let var = self.resolve_name(&assign.ident, span);
+                if let Some(Var { ty: Some(ty), defined, .. }) = var {
+                    if let Some(val) = val.as_ref().filter(|val| !val.agrees(&ty)) {
+                        self.error(span, ExceptionKind::InvalidType(ty.clone(), val.clone()));
+                        if let Some(defined) = defined {
+                            self.label_at(
+                                defined,
+                                format!("variable first defined with type {} here", ty.pretty()),
+                            );
+                        }
+                    }
+                }
+            }
+        }
+        val
+    }
+
+    /// Check `record.field = val`, which leaves the updated record
+    fn assign_fields(&mut self, assign: &Assign, span: &Span) -> Option<Type> {
+        let val = self.expr(&assign.val);
+        let record = self.resolve_name(&assign.ident, span)?.ty?;
+
+        let mut field = record.clone();
+        for name in &assign.fields {
+            // Only records have fields which can be assigned
+            let kind = match (&field, field_ty(&field, name)) {
+                (Type::Record(_, _), Some(ty)) => {
+                    field = self.resolve(&ty);
+                    continue;
+                }
+                (_, Some(_)) => ExceptionKind::ReadOnlyField(field, name.clone()),
+                (_, None) => ExceptionKind::FieldNotFound(field, name.clone()),
+            };
+            self.error(span, kind);
+            return None;
+        }
+
+        let op = match assign.assign_op {
+            AssignOp::Eq => None,
+            AssignOp::PlusEq => Some(Op::Add),
+            AssignOp::SubEq => Some(Op::Sub),
+            AssignOp::MulEq => Some(Op::Mul),
+            AssignOp::DivEq => Some(Op::Div),
+        };
+        let val = match op {
+            Some(op) => self.op(span, op, &[Some(field.clone()), val]),
+            None => val,
+        };
+        if let Some(val) = val.filter(|val| !val.agrees(&field)) {
+            self.error(span, ExceptionKind::InvalidType(field, val));
+        }
+        Some(record)
+    }
+
+    fn fn_def(&mut self, def: &FnDef) {
+        self.scopes.push(HashMap::new());
+        self.declared.push(declared_names(&def.stmts));
+        // Inside the body, a type parameter is a type of its own, even if it shares a name with an
+        // alias outside
+        let generics = def.generics.iter().map(|param| (param.to_string(), Type::Named(param.clone())));
+        self.aliases.push(generics.collect());
+        for arg in &def.args {
+            let ty = self.resolve(&arg.ty.node);
+            self.define(&arg.name, Some(ty), arg.ty.span.clone());
+        }
+
+        // The limit is checked after every statement, so any of them might be the one returned
+        let returned = def.stmts.iter().map(|stmt| self.stmt(stmt)).collect::<Vec<_>>();
+
+        if let Some(limit) = self.expr(&def.limit) {
+            if limit != Type::named("bit") {
+                self.error(&def.limit.span, ExceptionKind::InvalidType(Type::named("bit"), limit));
+            }
+        }
+
+        let ret = &self.resolve(&def.ret.node);
+        let last = match returned.last() {
+            Some(last) => last.clone(),
+            None => Some(Type::named("null")),
+        };
+        if !returned.iter().any(|ty| ty.as_ref().is_none_or(|ty| ret.matches(ty))) {
+            if let Some(last) = last.filter(|last| !ret.matches(last)) {
+                let span = def.stmts.last().map_or(def.span.clone(), |stmt| stmt.span.clone());
+                self.error(&span, ExceptionKind::InvalidType(ret.clone(), last));
+                self.label(&def.ret.span, String::from("return type declared here"));
+            }
+        }
+
+        self.aliases.pop();
+        self.declared.pop();
+        self.scopes.pop();
+    }
+}
+
+/// Every name `stmts` define, at any point in the block
+fn declared_names(stmts: &[Spanned<Stmt>]) -> HashSet<String> {
+    let mut names = HashSet::new();
+    for stmt in stmts {
+        match &stmt.node {
+            Stmt::FnDef(def) => {
+                names.insert(def.name.to_string());
+            }
+            Stmt::Assign(assign) if !matches!(assign.ty, AssignTy::Default) => {
+                names.insert(assign.ident.to_string());
+            }
+            Stmt::Assign(_) | Stmt::Expr(_) => (),
+            Stmt::Once(stmts) | Stmt::Sync(stmts) => names.extend(declared_names(stmts)),
+            Stmt::TypeDef(name, ty) => {
+                names.insert(name.to_string());
+                if let Type::Union(_, variants) = &ty.node {
+                    names.extend(variants.iter().map(|(variant, _)| variant.to_string()));
+                }
+            }
+        }
+    }
+    names
+}
+
+/// The type an operator produces, or `None` if it isn't defined between the operands. This
+/// mirrors the operators each value type provides at runtime.
+fn op_result(op: Op, operands: &[Type]) -> Option<Type> {
+    // An empty array's elements are `any`, so prefer an operand whose type says more
+    let empty = Type::Array(Box::new(Type::named("any")));
+    let ty = operands.iter().find(|ty| **ty != empty).unwrap_or(&operands[0]);
+    if operands.iter().any(|other| !other.agrees(ty)) {
+        return None;
+    }
+
+    let is = |names: &[&str]| names.iter().any(|name| *ty == Type::named(name));
+    let ok = match op {
+        Op::Eq | Op::Neq => return Some(Type::named("bit")),
+        Op::Lt | Op::Gt | Op::LtEq | Op::GtEq => {
+            return is(&["int", "bigint", "float", "char", "chararray"]).then(|| Type::named("bit"));
+        }
+        Op::Inv => is(&["int", "bigint", "bit"]),
+        Op::Neg => is(&["int", "bigint", "float"]),
+        Op::Add => is(&["int", "bigint", "float", "chararray"]) || matches!(ty, Type::Array(_)),
+        Op::Sub | Op::Mul | Op::Div => is(&["int", "bigint", "float"]),
+        Op::BitAnd | Op::BitOr | Op::BitXor => is(&["int", "bigint", "bit"]),
+        Op::And | Op::Or => is(&["bit"]),
+    };
+    ok.then(|| ty.clone())
+}
+
+/// The type of a builtin field, mirroring `ValItem::get_field` for each value type
+fn field_ty(ty: &Type, name: &str) -> Option<Type> {
+    match (ty, name) {
+        (Type::Array(_), "len") => Some(Type::named("int")),
+        (ty, "len") if *ty == Type::named("chararray") => Some(Type::named("int")),
+        (Type::Fn(_, _), "name") => Some(Type::named("chararray")),
+        (Type::Fn(_, _), "arity") => Some(Type::named("int")),
+        (ty, "open") if *ty == Type::named("file") => Some(Type::named("bit")),
+        (Type::Record(_, fields), name) => {
+            fields.iter().find(|(field, _)| &**field == name).map(|(_, ty)| ty.clone())
+        }
+        _ => None,
+    }
+}
+
+impl FnDef {
+    /// The type of this function as seen by callers, where its type parameters accept anything
+    fn ty(&self) -> Type {
+        let any = self.generics.iter().map(|param| (param.clone(), Some(Type::named("any"))));
+        Type::Fn(Box::new(self.ret.node.clone()), self.arg_tys()).substitute(&any.collect::<Vec<_>>())
+    }
+}
+
+impl File {
+    /// Check the types in this file without running it. Names not defined by the file are looked
+    /// up in `env`, and anything whose type can't be known ahead of time is assumed to be correct.
+    /// `imports` holds what each of the file's imports defines, or `None` where that isn't known.
+    /// Also gives what the file leaves at its top level, for files importing it.
+    pub(crate) fn check(
+        &self,
+        env: &Env<'_>,
+        imports: &[Option<Rc<Exports>>],
+    ) -> (Vec<Diagnostic>, Exports) {
+        let mut checker = Checker {
+            source: self.source,
+            env,
+            scopes: vec![HashMap::new()],
+            declared: vec![declared_names(&self.stmts)],
+            aliases: vec![HashMap::new()],
+            diagnostics: Vec::new(),
+        };
+        for (import, exports) in self.imports.iter().zip(imports) {
+            let path = &import.path().node;
+            match import.binding() {
+                ImportBinding::Namespace(name) => {
+                    let module = exports.as_ref().map(|exports| (path.clone(), Rc::clone(exports)));
+                    let ty = module.as_ref().map(|_| Type::named("module"));
+                    checker.define(name, ty, import.span.clone());
+                    checker.scopes[0].get_mut(&**name).unwrap().module = module;
+                }
+                ImportBinding::Select(names) => {
+                    for (name, def) in names {
+                        match exports.as_ref().map(|exports| exports.0.get(&*def.node)) {
+                            Some(Some(var)) => {
+                                checker.scopes[0].insert(name.to_string(), var.clone());
+                            }
+                            Some(None) => {
+                                let kind = ExceptionKind::NotExported(path.clone(), def.node.clone());
+                                checker.error(&def.span, kind);
+                                checker.define(name, None, def.span.clone());
+                            }
+                            None => checker.define(name, None, def.span.clone()),
+                        }
+                    }
+                }
+            }
+        }
+        for stmt in &self.stmts {
+            checker.stmt(stmt);
+        }
+        let exports = Exports(checker.scopes.swap_remove(0));
+        (checker.diagnostics, exports)
+    }
+}
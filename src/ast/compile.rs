@@ -0,0 +1,406 @@
+use std::mem;
+use std::rc::Rc;
+
+use super::*;
+use crate::diagnostic::SourceId;
+use crate::interp::Op;
+use crate::vm::{Chunk, Const, FnProto, Instr};
+
+struct Compiler {
+    chunk: Chunk,
+    /// The span of the innermost node being compiled
+    span: Span,
+}
+
+impl Compiler {
+    fn new(source: SourceId, span: Span) -> Compiler {
+        Compiler {
+            chunk: Chunk {
+                source,
+                ..Chunk::default()
+            },
+            span,
+        }
+    }
+
+    fn emit(&mut self, instr: Instr) -> u32 {
+        self.chunk.code.push(instr);
+        self.chunk.spans.push(self.span.clone());
+        self.here() - 1
+    }
+
+    fn expr(&mut self, expr: &Spanned<Expr>) {
+        let outer = mem::replace(&mut self.span, expr.span.clone());
+        expr.node.compile(self);
+        self.span = outer;
+    }
+
+    fn stmt(&mut self, stmt: &Spanned<Stmt>) {
+        let outer = mem::replace(&mut self.span, stmt.span.clone());
+        stmt.node.compile(self);
+        self.span = outer;
+    }
+
+    fn here(&self) -> u32 {
+        self.chunk.code.len() as u32
+    }
+
+    /// Point a previously emitted jump at the next instruction to be emitted
+    fn patch(&mut self, jump: u32) {
+        let target = self.here();
+        match &mut self.chunk.code[jump as usize] {
+            Instr::Jump(t) | Instr::JumpIfNotFirst(t) | Instr::ShortCircuit(_, t) => *t = target,
+            instr => unreachable!("Attempted to patch non-jump {:?}", instr),
+        }
+    }
+
+    fn name(&mut self, name: &Ident) -> u32 {
+        let names = &mut self.chunk.names;
+        let idx = names.iter().position(|n| n == name).unwrap_or_else(|| {
+            names.push(name.clone());
+            names.len() - 1
+        });
+        idx as u32
+    }
+
+    fn constant(&mut self, c: Const) -> u32 {
+        self.chunk.consts.push(c);
+        self.chunk.consts.len() as u32 - 1
+    }
+
+    fn ty(&mut self, ty: &Type) -> u32 {
+        self.chunk.types.push(ty.clone());
+        self.chunk.types.len() as u32 - 1
+    }
+
+    fn field_list(&mut self, fields: Vec<Ident>) -> u32 {
+        self.chunk.field_lists.push(fields);
+        self.chunk.field_lists.len() as u32 - 1
+    }
+
+    fn proto(&mut self, proto: FnProto) -> u32 {
+        self.chunk.protos.push(Rc::new(proto));
+        self.chunk.protos.len() as u32 - 1
+    }
+
+    /// Compile statements whose values are discarded, such as the contents of a block
+    fn block(&mut self, stmts: &[Spanned<Stmt>]) {
+        for stmt in stmts {
+            self.stmt(stmt);
+            self.emit(Instr::Pop);
+        }
+    }
+
+    fn finish(self) -> Chunk {
+        self.chunk
+    }
+}
+
+impl Assign {
+    fn compile(&self, c: &mut Compiler) {
+        if !self.fields.is_empty() {
+            return self.compile_fields(c);
+        }
+
+        let name = c.name(&self.ident);
+        let op = match self.assign_op {
+            AssignOp::Eq => {
+                c.expr(&self.val);
+                c.emit(match self.ty {
+                    AssignTy::Unique => Instr::Define(name),
+                    AssignTy::CarryOver => Instr::DefineFirst(name),
+                    AssignTy::Default => Instr::Assign(name),
+                });
+                return;
+            }
+            AssignOp::PlusEq => Op::Add,
+            AssignOp::SubEq => Op::Sub,
+            AssignOp::MulEq => Op::Mul,
+            AssignOp::DivEq => Op::Div,
+        };
+
+        c.emit(Instr::Load(name));
+        c.expr(&self.val);
+        c.emit(Instr::BinOp(op));
+        c.emit(Instr::Assign(name));
+    }
+
+    /// Compile `a.b.c = val`. Records are values, so this pushes `a` and `a.b`, sets `c` on the
+    /// latter, sets `b` on the former, and assigns the result back to `a`.
+    fn compile_fields(&self, c: &mut Compiler) {
+        let name = c.name(&self.ident);
+        let fields = self.fields.iter().map(|f| c.name(f)).collect::<Vec<_>>();
+        let (last, path) = fields.split_last().unwrap();
+
+        for depth in 0..fields.len() {
+            c.emit(Instr::Load(name));
+            for &field in &path[..depth] {
+                c.emit(Instr::GetField(field));
+            }
+        }
+        // The top of the stack is now the record owning the last field
+        let op = match self.assign_op {
+            AssignOp::Eq => None,
+            AssignOp::PlusEq => Some(Op::Add),
+            AssignOp::SubEq => Some(Op::Sub),
+            AssignOp::MulEq => Some(Op::Mul),
+            AssignOp::DivEq => Some(Op::Div),
+        };
+        if let Some(op) = op {
+            c.emit(Instr::Load(name));
+            for &field in &fields {
+                c.emit(Instr::GetField(field));
+            }
+            c.expr(&self.val);
+            c.emit(Instr::BinOp(op));
+        } else {
+            c.expr(&self.val);
+        }
+
+        c.emit(Instr::SetField(*last));
+        for &field in path.iter().rev() {
+            c.emit(Instr::SetField(field));
+        }
+        c.emit(Instr::Assign(name));
+    }
+}
+
+impl Expr {
+    fn compile(&self, c: &mut Compiler) {
+        match self {
+            Expr::FnCall(call) => call.compile(c),
+            Expr::Call(callee, args) => {
+                c.expr(callee);
+                for arg in args {
+                    c.expr(arg);
+                }
+                c.emit(Instr::Call(args.len() as u32));
+            }
+            Expr::Write(ty, args) => {
+                let (w, expr) = match ty {
+                    WriteTy::Console => ("write_console", None),
+                    WriteTy::Error => ("write_error", None),
+                    WriteTy::RawFile => ("write_honk", None),
+                    WriteTy::Other(expr) => ("write_io", Some(expr)),
+                };
+
+                let name = c.name(&Ident(w.to_string()));
+                c.emit(Instr::Load(name));
+
+                let mut argc = args.len();
+                if let Some(expr) = expr {
+                    c.expr(expr);
+                    argc += 1;
+                }
+                for arg in args {
+                    c.expr(arg);
+                }
+
+                c.emit(Instr::Call(argc as u32));
+                c.emit(Instr::Pop);
+                c.emit(Instr::Null);
+            }
+            Expr::Read(ty, amount) => {
+                let (r, expr) = match ty {
+                    ReadTy::Console => ("read_console", None),
+                    ReadTy::Other(expr) => ("read_io", Some(expr)),
+                };
+
+                let name = c.name(&Ident(r.to_string()));
+                c.emit(Instr::Load(name));
+
+                let mut argc = 1;
+                if let Some(expr) = expr {
+                    c.expr(expr);
+                    argc += 1;
+                }
+                // The builtins take the amount as a count, or the name of how far to read
+                match amount {
+                    ReadAmount::Count(count) => c.expr(count),
+                    ReadAmount::Line | ReadAmount::All => {
+                        let mode = if let ReadAmount::Line = amount { "line" } else { "all" };
+                        let idx = c.constant(Const::CharArray(mode.to_string()));
+                        c.emit(Instr::Const(idx));
+                    }
+                }
+
+                c.emit(Instr::Call(argc));
+            }
+            Expr::Literal(lit) => lit.compile(c),
+            Expr::Ident(i) => {
+                let name = c.name(i);
+                c.emit(Instr::Load(name));
+            }
+            &Expr::UnOp(op, ref expr) => {
+                c.expr(expr);
+                c.emit(Instr::UnOp(op.into()));
+            }
+            &Expr::BinOp(ref left, op @ (BinOp::And | BinOp::Or), ref right) => {
+                // `false && x` and `true || x` are decided by the left side alone. Otherwise the
+                // operator still runs, so the right side is checked to be a bit.
+                c.expr(left);
+                let jump = c.emit(Instr::ShortCircuit(matches!(op, BinOp::Or), 0));
+                c.expr(right);
+                c.emit(Instr::BinOp(op.into()));
+                c.patch(jump);
+            }
+            &Expr::BinOp(ref left, op, ref right) => {
+                c.expr(left);
+                c.expr(right);
+                c.emit(Instr::BinOp(op.into()));
+            }
+            Expr::Field(expr, field) => {
+                c.expr(expr);
+                let name = c.name(field);
+                c.emit(Instr::GetField(name));
+            }
+            Expr::Index(expr, idx) => {
+                c.expr(expr);
+                c.expr(idx);
+                c.emit(Instr::Index);
+            }
+            Expr::Record(name, fields) => {
+                for (_, val) in fields {
+                    c.expr(val);
+                }
+                let name = c.name(name);
+                let fields = c.field_list(fields.iter().map(|(f, _)| f.clone()).collect());
+                c.emit(Instr::MakeRecord(name, fields));
+            }
+        }
+    }
+}
+
+impl File {
+    /// Lower this file to a chunk, which returns the value of the last statement
+    pub fn compile(&self) -> Chunk {
+        let end = self.stmts.last().map_or(0, |stmt| stmt.span.end);
+        let mut c = Compiler::new(self.source, 0..end);
+
+        for (idx, stmt) in self.stmts.iter().enumerate() {
+            if idx != 0 {
+                c.emit(Instr::Pop);
+            }
+            c.stmt(stmt);
+        }
+        if self.stmts.is_empty() {
+            c.emit(Instr::Null);
+        }
+        c.emit(Instr::Return);
+
+        c.finish()
+    }
+}
+
+impl FnCall {
+    fn compile(&self, c: &mut Compiler) {
+        let name = c.name(&self.name);
+        c.emit(Instr::Load(name));
+        for arg in &self.args {
+            c.expr(arg);
+        }
+        c.emit(Instr::Call(self.args.len() as u32));
+    }
+}
+
+impl FnDef {
+    /// Lower this function to a prototype. The body loops forever, checking the limit after each
+    /// statement, until the limit returns from it.
+    pub fn compile(&self, source: SourceId) -> FnProto {
+        let mut limit = Compiler::new(source, self.limit.span.clone());
+        limit.expr(&self.limit);
+        limit.emit(Instr::Return);
+
+        let mut body = Compiler::new(source, self.span.clone());
+        for stmt in &self.stmts {
+            body.stmt(stmt);
+            // Attribute the return to the statement whose value is being returned
+            body.span = stmt.span.clone();
+            body.emit(Instr::CheckLimit);
+            body.span = self.span.clone();
+        }
+        if self.stmts.is_empty() {
+            body.emit(Instr::Null);
+            body.emit(Instr::CheckLimit);
+        }
+        body.emit(Instr::NextIter);
+        body.emit(Instr::Jump(0));
+
+        FnProto {
+            name: self.name.clone(),
+            generics: self.generics.clone(),
+            ret: self.ret.clone(),
+            args: self
+                .args
+                .iter()
+                .map(|arg| (arg.name.clone(), arg.ty.clone()))
+                .collect(),
+            limit: limit.finish(),
+            body: body.finish(),
+        }
+    }
+}
+
+impl Literal {
+    fn compile(&self, c: &mut Compiler) {
+        let constant = match self {
+            Literal::Int(i) => Const::Int(*i),
+            Literal::Float(f) => Const::Float(*f),
+            Literal::BigInt(i) => Const::BigInt(i.clone()),
+            Literal::Char(ch) => Const::Char(*ch),
+            Literal::CharArray(s) => Const::CharArray(s.clone()),
+            Literal::Bit(b) => Const::Bit(*b),
+            Literal::Fn(f) => {
+                let proto = c.proto(f.compile(c.chunk.source));
+                c.emit(Instr::MakeFn(proto));
+                return;
+            }
+            Literal::Array(a) => {
+                for expr in a {
+                    c.expr(expr);
+                }
+                c.emit(Instr::MakeArray(a.len() as u32));
+                return;
+            }
+        };
+
+        let idx = c.constant(constant);
+        c.emit(Instr::Const(idx));
+    }
+}
+
+impl Stmt {
+    /// Compile this statement, leaving its value on the stack
+    fn compile(&self, c: &mut Compiler) {
+        match self {
+            Stmt::FnDef(def) => {
+                let proto = c.proto(def.compile(c.chunk.source));
+                let name = c.name(&def.name);
+                c.emit(Instr::MakeFn(proto));
+                c.emit(Instr::Define(name));
+                c.emit(Instr::Pop);
+                c.emit(Instr::Null);
+            }
+            Stmt::Assign(assign) => assign.compile(c),
+            Stmt::Sync(sync) => {
+                c.emit(Instr::SyncStart);
+                c.block(sync);
+                c.emit(Instr::SyncEnd);
+                c.emit(Instr::Null);
+            }
+            Stmt::Once(once) => {
+                let jump = c.emit(Instr::JumpIfNotFirst(0));
+                c.block(once);
+                c.patch(jump);
+                c.emit(Instr::Null);
+            }
+            Stmt::Expr(expr) => c.expr(expr),
+            Stmt::TypeDef(name, ty) => {
+                let name = c.name(name);
+                let ty = c.ty(ty);
+                c.emit(Instr::DefineType(name, ty));
+                c.emit(Instr::Null);
+            }
+        }
+    }
+}
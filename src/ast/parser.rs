@@ -1,7 +1,10 @@
+#![allow(clippy::result_large_err)]
+
 use chumsky::prelude::*;
 use std::str::FromStr;
 
 use super::*;
+use crate::diagnostic::SourceId;
 use crate::token::Token;
 
 macro_rules! Parser {
@@ -11,25 +14,108 @@ macro_rules! Parser {
 }
 
 impl Assign {
-    pub fn parser<'a>(expr: Parser!['a, Expr]) -> Parser!['a, Self] {
+    pub fn parser<'a>(expr: Parser!['a, Spanned<Expr>]) -> Parser!['a, Self] {
         just(Token::Unique)
             .to(AssignTy::Unique)
             .or(just(Token::CarryOver).to(AssignTy::CarryOver))
             .or_not()
             .map(|ty| ty.unwrap_or(AssignTy::Default))
             .then(Ident::parser())
+            .then(just(Token::Dot).ignore_then(Ident::parser()).repeated())
+            .validate(|((ty, ident), fields), span, emit| {
+                if !fields.is_empty() && !matches!(ty, AssignTy::Default) {
+                    emit(Simple::custom(span, "Only an existing variable can have its fields assigned"));
+                }
+                ((ty, ident), fields)
+            })
             .then(AssignOp::parser())
             .then(expr)
             .then_ignore(just(Token::SemiColon))
-            .map(|(((ty, ident), assign_op), val)| Assign {
+            .map(|((((ty, ident), fields), assign_op), val)| Assign {
                 ty,
                 ident,
+                fields,
                 assign_op,
                 val,
             })
     }
 }
 
+/// Parse `{ name: item, ... }`, as used to declare and construct records. Each field can only be
+/// given once.
+fn record_fields<'a, T: 'a>(item: Parser!['a, T]) -> Parser!['a, Vec<(Ident, T)>] {
+    Ident::parser()
+        .map_with_span(Spanned::new)
+        .then_ignore(just(Token::Colon))
+        .then(item)
+        .separated_by(just(Token::Comma))
+        .allow_trailing()
+        .delimited_by(just(Token::OpenCurly), just(Token::CloseCurly))
+        .validate(|fields, _, emit| unique_names(fields, "Field", emit))
+}
+
+/// Parse `Variant(type) | Other | ...`. A single variant without a payload is just a type name,
+/// so isn't treated as a union.
+fn union_variants<'a>() -> Parser!['a, Vec<(Ident, Option<Type>)>] {
+    Ident::parser()
+        .map_with_span(Spanned::new)
+        .then(
+            Type::parser()
+                .delimited_by(just(Token::OpenParen), just(Token::CloseParen))
+                .or_not(),
+        )
+        .separated_by(just(Token::Pipe))
+        .at_least(1)
+        .try_map(|variants, span| match variants.as_slice() {
+            [(_, None)] => Err(Simple::custom(span, "A union needs a payload or another variant")),
+            _ => Ok(variants),
+        })
+        .validate(|variants, _, emit| unique_names(variants, "Variant", emit))
+}
+
+/// Drop any items whose name was already used, reporting each one
+fn unique_names<'a, T>(
+    items: Vec<(Spanned<Ident>, T)>,
+    what: &str,
+    emit: &mut dyn FnMut(Simple<Token<'a>>),
+) -> Vec<(Ident, T)> {
+    let mut seen: Vec<Ident> = Vec::new();
+    items
+        .into_iter()
+        .filter_map(|(name, item)| {
+            if seen.contains(&name.node) {
+                let msg = format!("{} `{}` is given more than once", what, &*name.node);
+                emit(Simple::custom(name.span, msg));
+                None
+            } else {
+                seen.push(name.node.clone());
+                Some((name.node, item))
+            }
+        })
+        .collect()
+}
+
+/// Parse the type parameters of a function, like `<T, U>`, if it has any
+fn generics<'a>() -> Parser!['a, Vec<Ident>] {
+    Ident::parser()
+        .map_with_span(|name, span| (Spanned::new(name, span), ()))
+        .separated_by(just(Token::Comma))
+        .at_least(1)
+        .delimited_by(just(Token::Lt), just(Token::Gt))
+        .validate(|params, _, emit| unique_names(params, "Type parameter", emit))
+        .map(|params| params.into_iter().map(|(name, ())| name).collect())
+        .or_not()
+        .map(Option::unwrap_or_default)
+}
+
+/// What a `type` statement binds its name to
+#[derive(Clone)]
+enum TypeDefBody {
+    Record(Vec<(Ident, Type)>),
+    Union(Vec<(Ident, Option<Type>)>),
+    Alias(Type),
+}
+
 impl AssignOp {
     pub fn parser<'a>() -> Parser!['a, Self] {
         just(Token::Eq)
@@ -59,56 +145,264 @@ impl BinOp {
             .to(BinOp::Mul)
             .or(just(Token::Slash).to(BinOp::Div))
     }
+
+    pub fn ord_parser<'a>() -> Parser!['a, Self] {
+        just(Token::LtEq)
+            .to(BinOp::LtEq)
+            .or(just(Token::GtEq).to(BinOp::GtEq))
+            .or(just(Token::Lt).to(BinOp::Lt))
+            .or(just(Token::Gt).to(BinOp::Gt))
+    }
+
+    pub fn bit_and_parser<'a>() -> Parser!['a, Self] {
+        just(Token::And).to(BinOp::BitAnd)
+    }
+
+    pub fn bit_xor_parser<'a>() -> Parser!['a, Self] {
+        just(Token::Caret).to(BinOp::BitXor)
+    }
+
+    pub fn bit_or_parser<'a>() -> Parser!['a, Self] {
+        just(Token::Pipe).to(BinOp::BitOr)
+    }
+
+    pub fn and_parser<'a>() -> Parser!['a, Self] {
+        just(Token::AndAnd).to(BinOp::And)
+    }
+
+    pub fn or_parser<'a>() -> Parser!['a, Self] {
+        just(Token::PipePipe).to(BinOp::Or)
+    }
 }
 
 impl Expr {
-    pub fn parser<'a>() -> Parser!['a, Self] {
-        recursive(|expr| {
-            let atom = Literal::parser(expr.clone())
-                .map(Expr::Literal)
-                .or(just(Token::Ident("write"))
-                    .ignore_then(just(Token::OpenParen))
-                    .ignore_then(WriteTy::parser(expr.clone()))
-                    .then_ignore(just(Token::Comma))
-                    .then(expr.clone().separated_by(just(Token::Comma)))
-                    .then_ignore(just(Token::CloseParen))
-                    .map(|(ty, exprs)| Expr::Write(ty, exprs)))
-                .or(FnCall::parser(expr).map(Expr::FnCall))
-                .or(Ident::parser().map(Expr::Ident))
-                .boxed();
+    pub fn parser<'a>() -> Parser!['a, Spanned<Self>] {
+        Expr::parsers().0
+    }
 
-            let unary = UnOp::parser()
-                .repeated()
-                .then(atom)
-                .foldr(|op, expr| Expr::UnOp(op, Box::new(expr)));
+    /// Parsers for a full expression, and for an expression between a limit's pipes. In a limit,
+    /// `|` ends the limit rather than being bitwise or, unless it's inside parentheses.
+    pub fn parsers<'a>() -> (Parser!['a, Spanned<Self>], Parser!['a, Spanned<Self>]) {
+        let mut expr: Recursive<'a, Token<'a>, Spanned<Expr>, Simple<Token<'a>>> =
+            Recursive::declare();
+        let mut limit: Recursive<'a, Token<'a>, Spanned<Expr>, Simple<Token<'a>>> =
+            Recursive::declare();
 
-            let bin_parsers = [
-                BinOp::mul_parser().boxed(),
-                BinOp::add_parser().boxed(),
-                BinOp::cmp_parser().boxed(),
-            ];
+        expr.define(Expr::binary_parser(expr.clone(), limit.clone(), true));
+        limit.define(Expr::binary_parser(expr.clone(), limit.clone(), false));
 
-            let mut binary = unary.boxed();
+        (expr, limit)
+    }
 
-            for op_parser in bin_parsers {
-                binary = binary
-                    .clone()
-                    .then(op_parser.then(binary).repeated())
-                    .foldl(|left, (op, right)| Expr::BinOp(Box::new(left), op, Box::new(right)))
-                    .boxed();
-            }
+    fn binary_parser<'a>(
+        expr: Parser!['a, Spanned<Self>],
+        limit: Parser!['a, Spanned<Self>],
+        allow_pipe: bool,
+    ) -> Parser!['a, Spanned<Self>] {
+        let atom = Literal::parser(expr.clone(), limit)
+            .map(Expr::Literal)
+            .or(just(Token::Ident("write"))
+                .ignore_then(just(Token::OpenParen))
+                .ignore_then(WriteTy::parser(expr.clone()))
+                .then_ignore(just(Token::Comma))
+                .then(expr.clone().separated_by(just(Token::Comma)))
+                .then_ignore(just(Token::CloseParen))
+                .map(|(ty, exprs)| Expr::Write(ty, exprs)))
+            .or(just(Token::Ident("read"))
+                .ignore_then(just(Token::OpenParen))
+                .ignore_then(ReadTy::parser(expr.clone()))
+                .then(just(Token::Comma)
+                    .ignore_then(ReadAmount::parser(expr.clone()))
+                    .or_not())
+                .then_ignore(just(Token::CloseParen))
+                .map(|(ty, amount)| Expr::Read(ty, amount.unwrap_or(ReadAmount::Line))))
+            .or(Ident::parser()
+                .then(record_fields(expr.clone()))
+                .map(|(name, fields)| Expr::Record(name, fields)))
+            .or(FnCall::parser(expr.clone()).map(Expr::FnCall))
+            .or(Ident::parser().map(Expr::Ident))
+            .map_with_span(Spanned::new)
+            .or(expr
+                .clone()
+                .delimited_by(just(Token::OpenParen), just(Token::CloseParen))
+                // The node covers the parentheses too, so diagnostics underline all of it
+                .map_with_span(|expr: Spanned<Expr>, span| Spanned::new(expr.node, span)))
+            .boxed();
 
-            binary
-        })
+        // The index after `#` is a single atom, optionally negated, so `a#i.len` is the length of
+        // `a#i` and `a#-1` indexes with -1
+        let index = just(Token::Dash)
+            .map_with_span(|_, span: Span| span)
+            .or_not()
+            .then(atom.clone())
+            .map(|(neg, idx)| match neg {
+                Some(neg) => {
+                    let span = neg.start..idx.span.end;
+                    Spanned::new(Expr::UnOp(UnOp::Neg, Box::new(idx)), span)
+                }
+                None => idx,
+            });
+        let postfix = atom
+            .clone()
+            .then(
+                just(Token::Dot)
+                    .ignore_then(Ident::parser())
+                    .map(Postfix::Field)
+                    .or(just(Token::Hash).ignore_then(index).map(Postfix::Index))
+                    .or(expr
+                        .clone()
+                        .separated_by(just(Token::Comma))
+                        .delimited_by(just(Token::OpenParen), just(Token::CloseParen))
+                        .map(Postfix::Call))
+                    .map_with_span(|postfix, span: Span| (postfix, span.end))
+                    .repeated(),
+            )
+            .foldl(|expr, (postfix, end)| {
+                let span = expr.span.start..end;
+                let expr = Box::new(expr);
+                match postfix {
+                    Postfix::Field(name) => Spanned::new(Expr::Field(expr, name), span),
+                    Postfix::Index(idx) => Spanned::new(Expr::Index(expr, Box::new(idx)), span),
+                    Postfix::Call(args) => Spanned::new(Expr::Call(expr, args), span),
+                }
+            });
+
+        let unary = UnOp::parser()
+            .map_with_span(|op, span| (op, span))
+            .repeated()
+            .then(postfix)
+            .foldr(|(op, span), expr| {
+                let span = span.start..expr.span.end;
+                Spanned::new(Expr::UnOp(op, Box::new(expr)), span)
+            });
+
+        // From tightest to loosest binding
+        let mut bin_parsers = vec![
+            BinOp::mul_parser().boxed(),
+            BinOp::add_parser().boxed(),
+            BinOp::bit_and_parser().boxed(),
+            BinOp::bit_xor_parser().boxed(),
+        ];
+        if allow_pipe {
+            bin_parsers.push(BinOp::bit_or_parser().boxed());
+        }
+        bin_parsers.extend([
+            BinOp::ord_parser().boxed(),
+            BinOp::cmp_parser().boxed(),
+            BinOp::and_parser().boxed(),
+            BinOp::or_parser().boxed(),
+        ]);
+
+        let mut binary = unary.boxed();
+
+        for op_parser in bin_parsers {
+            binary = binary
+                .clone()
+                .then(op_parser.then(binary).repeated())
+                .foldl(|left, (op, right)| {
+                    let span = left.span.start..right.span.end;
+                    Spanned::new(Expr::BinOp(Box::new(left), op, Box::new(right)), span)
+                })
+                .boxed();
+        }
+
+        binary
     }
 }
 
+/// An operation following an atom, before any unary operators are applied
+#[derive(Clone)]
+enum Postfix {
+    Field(Ident),
+    Index(Spanned<Expr>),
+    Call(Vec<Spanned<Expr>>),
+}
+
 impl File {
     pub fn parser<'a>() -> Parser!['a, Self] {
-        Stmt::parser(Expr::parser())
+        let (expr, limit) = Expr::parsers();
+        Import::parser()
+            .map_with_span(Spanned::new)
+            .map(TopLevel::Import)
+            .or(Stmt::parser(expr, limit).map(|stmt| TopLevel::Stmt(Box::new(stmt))))
             .repeated()
             .then_ignore(end())
-            .map(|stmts| File { stmts })
+            .validate(|items, _, emit| {
+                let mut imports = Vec::new();
+                let mut stmts = Vec::new();
+                for item in items {
+                    match item {
+                        TopLevel::Import(import) if !stmts.is_empty() => emit(Simple::custom(
+                            import.span,
+                            "Imports must come before any other statement",
+                        )),
+                        TopLevel::Import(import) => imports.push(import),
+                        TopLevel::Stmt(stmt) => stmts.push(*stmt),
+                    }
+                }
+                File {
+                    source: SourceId::default(),
+                    imports,
+                    stmts,
+                }
+            })
+    }
+}
+
+/// Something written at the top level of a file
+enum TopLevel {
+    Import(Spanned<Import>),
+    Stmt(Box<Spanned<Stmt>>),
+}
+
+impl Import {
+    pub fn parser<'a>() -> Parser!['a, Self] {
+        let path = filter_map(|span: Span, tok| match tok {
+            Token::Str(s) => unescape(s, span.start).map(|path| Spanned::new(path, span)),
+            _ => Err(Simple::expected_input_found(span, [Some(Token::Str("..."))], Some(tok))),
+        });
+        let alias = just(Token::Ident("as")).ignore_then(Ident::parser().map_with_span(Spanned::new));
+
+        let select = Ident::parser()
+            .map_with_span(Spanned::new)
+            .then(alias.clone().or_not())
+            .map(|(name, alias)| (alias.unwrap_or_else(|| name.clone()), name))
+            .separated_by(just(Token::Comma))
+            .allow_trailing()
+            .delimited_by(just(Token::OpenCurly), just(Token::CloseCurly))
+            .validate(|names, _, emit| unique_names(names, "Name", emit))
+            .then_ignore(just(Token::Ident("from")))
+            .then(path)
+            .map(|(names, path)| Import {
+                path,
+                binding: ImportBinding::Select(names),
+            });
+
+        // Without `as`, the module is named after its file
+        let namespace = path
+            .then(alias.or_not())
+            .validate(|(path, alias), span, emit| {
+                let name = alias.map(|alias| alias.node).or_else(|| {
+                    let stem = std::path::Path::new(&path.node).file_stem()?.to_str()?;
+                    let mut chars = stem.chars();
+                    let valid = chars.next().is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+                        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_');
+                    valid.then(|| Ident(stem.to_string()))
+                });
+                let name = name.unwrap_or_else(|| {
+                    let msg = format!("`{}` isn't a valid name, so the import needs one given with `as`", path.node);
+                    emit(Simple::custom(span, msg));
+                    Ident(String::new())
+                });
+                Import {
+                    path,
+                    binding: ImportBinding::Namespace(name),
+                }
+            });
+
+        just(Token::Ident("import"))
+            .ignore_then(select.or(namespace))
+            .then_ignore(just(Token::SemiColon))
     }
 }
 
@@ -116,13 +410,13 @@ impl FnArg {
     pub fn parser<'a>() -> Parser!['a, Self] {
         Ident::parser()
             .then_ignore(just(Token::Colon))
-            .then(Type::parser())
+            .then(Type::parser().map_with_span(Spanned::new))
             .map(|(name, ty)| FnArg { name, ty })
     }
 }
 
 impl FnCall {
-    pub fn parser<'a>(expr: Parser!['a, Expr]) -> Parser!['a, Self] {
+    pub fn parser<'a>(expr: Parser!['a, Spanned<Expr>]) -> Parser!['a, Self] {
         Ident::parser()
             .then(
                 expr.separated_by(just(Token::Comma))
@@ -133,28 +427,34 @@ impl FnCall {
 }
 
 impl FnDef {
-    pub fn parser<'a>(expr: Parser!['a, Expr], stmt: Parser!['a, Stmt]) -> Parser!['a, Self] {
+    pub fn parser<'a>(
+        limit: Parser!['a, Spanned<Expr>],
+        stmt: Parser!['a, Spanned<Stmt>],
+    ) -> Parser!['a, Self] {
         just(Token::Def)
             .ignore_then(Ident::parser())
+            .then(generics())
             .then_ignore(just(Token::Colon))
-            .then(Type::parser())
+            .then(Type::parser().map_with_span(Spanned::new))
             .then(
                 FnArg::parser()
                     .separated_by(just(Token::Comma))
                     .delimited_by(just(Token::OpenParen), just(Token::CloseParen)),
             )
             .then_ignore(just(Token::Arrow))
-            .then(expr.delimited_by(just(Token::Pipe), just(Token::Pipe)))
+            .then(limit.delimited_by(just(Token::Pipe), just(Token::Pipe)))
             .then(
                 stmt.repeated()
                     .delimited_by(just(Token::OpenBracket), just(Token::CloseBracket)),
             )
-            .map(|((((name, ret), args), limit), stmts)| FnDef {
+            .map_with_span(|(((((name, generics), ret), args), limit), stmts), span| FnDef {
                 name,
+                generics,
                 ret,
                 args,
                 limit: Box::new(limit),
                 stmts,
+                span,
             })
     }
 }
@@ -175,18 +475,152 @@ impl Ident {
     }
 }
 
+/// Decode the escape sequences in a quoted char or chararray literal, which starts at `start` in
+/// the source
+fn unescape<'a>(lit: &str, start: usize) -> Result<String, Simple<Token<'a>>> {
+    let inner = &lit[1..lit.len() - 1];
+    let mut out = String::with_capacity(inner.len());
+    let mut chars = inner.char_indices().peekable();
+
+    while let Some((idx, c)) = chars.next() {
+        if c != '\\' {
+            out.push(c);
+            continue;
+        }
+
+        // The quote comes before the contents
+        let escape_start = start + 1 + idx;
+        let next = chars.next().map(|(_, c)| c);
+        let decoded = match next {
+            Some('n') => Some('\n'),
+            Some('t') => Some('\t'),
+            Some('r') => Some('\r'),
+            Some('0') => Some('\0'),
+            Some(c @ ('\\' | '"' | '\'')) => Some(c),
+            Some('u') => {
+                let mut digits = None;
+                if chars.next_if(|(_, c)| *c == '{').is_some() {
+                    let mut hex = String::new();
+                    while let Some((_, c)) = chars.next_if(|(_, c)| *c != '}') {
+                        hex.push(c);
+                    }
+                    if chars.next_if(|(_, c)| *c == '}').is_some() {
+                        digits = Some(hex);
+                    }
+                }
+                let end = chars.peek().map_or(inner.len(), |(idx, _)| *idx);
+                let span = escape_start..start + 1 + end;
+                let hex = digits.ok_or_else(|| {
+                    Simple::custom(span.clone(), "Unicode escapes must be written like `\\u{1F600}`")
+                })?;
+                let c = (1..=6)
+                    .contains(&hex.len())
+                    .then(|| u32::from_str_radix(&hex, 16).ok())
+                    .flatten()
+                    .and_then(char::from_u32)
+                    .ok_or_else(|| {
+                        Simple::custom(span, format!("`{}` is not a valid unicode code point", hex))
+                    })?;
+                Some(c)
+            }
+            _ => None,
+        };
+
+        match decoded {
+            Some(c) => out.push(c),
+            None => {
+                let len = '\\'.len_utf8() + next.map_or(0, char::len_utf8);
+                let escape = &inner[idx..idx + len];
+                return Err(Simple::custom(
+                    escape_start..escape_start + len,
+                    format!("Invalid escape sequence `{}`", escape),
+                ));
+            }
+        }
+    }
+
+    Ok(out)
+}
+
+/// Check that every `_` in the runs of digits making up `lit` sits between two digits
+fn check_separators<'a, 'g>(
+    lit: &str,
+    mut groups: impl Iterator<Item = &'g str>,
+    span: Span,
+) -> Result<(), Simple<Token<'a>>> {
+    if groups.any(|group| group.starts_with('_') || group.ends_with('_') || group.contains("__")) {
+        let msg = format!("`{}` has a `_` that isn't between two digits", lit);
+        return Err(Simple::custom(span, msg));
+    }
+    Ok(())
+}
+
+/// Split an integer or bigint literal into its digits, without `_` separators or the bigint
+/// suffix, and their radix
+fn int_digits<'a>(lit: &str, span: Span) -> Result<(String, u32), Simple<Token<'a>>> {
+    let unsuffixed = lit.strip_suffix('n').unwrap_or(lit);
+    let (digits, radix) = match unsuffixed.get(..2) {
+        Some("0x") => (&unsuffixed[2..], 16),
+        Some("0o") => (&unsuffixed[2..], 8),
+        Some("0b") => (&unsuffixed[2..], 2),
+        _ => (unsuffixed, 10),
+    };
+    if digits.replace('_', "").is_empty() {
+        return Err(Simple::custom(span, format!("`{}` has no digits", lit)));
+    }
+    check_separators(lit, std::iter::once(digits), span)?;
+    Ok((digits.replace('_', ""), radix))
+}
+
+/// Parse an integer literal, which may have a radix prefix and `_` separators
+fn parse_int<'a>(lit: &str, span: Span) -> Result<Literal, Simple<Token<'a>>> {
+    let (digits, radix) = int_digits(lit, span.clone())?;
+    i128::from_str_radix(&digits, radix)
+        .map(Literal::Int)
+        .map_err(|_| Simple::custom(span, format!("`{}` is too large to fit in an int", lit)))
+}
+
+/// Parse a bigint literal, which is an integer literal with an `n` suffix
+fn parse_bigint<'a>(lit: &str, span: Span) -> Result<Literal, Simple<Token<'a>>> {
+    let (digits, radix) = int_digits(lit, span)?;
+    // The lexer only accepts digits valid for the radix
+    let val = num_bigint::BigInt::parse_bytes(digits.as_bytes(), radix).unwrap_or_default();
+    Ok(Literal::BigInt(val))
+}
+
+fn parse_float<'a>(lit: &str, span: Span) -> Result<Literal, Simple<Token<'a>>> {
+    let groups = lit.split(['.', 'e', 'E', '+', '-']);
+    check_separators(lit, groups, span.clone())?;
+    f64::from_str(&lit.replace('_', ""))
+        .ok()
+        .filter(|f| f.is_finite())
+        .map(Literal::Float)
+        .ok_or_else(|| Simple::custom(span, format!("`{}` is too large to fit in a float", lit)))
+}
+
 impl Literal {
-    pub fn parser<'a>(expr: Parser!['a, Expr]) -> Parser!['a, Self] {
-        filter_map(|span, tok| match tok {
-            Token::Int(i) => Ok(Literal::Int(i128::from_str(i).unwrap())),
-            Token::Float(f) => Ok(Literal::Float(f64::from_str(f).unwrap())),
-            Token::Bit(b) => Ok(Literal::Bit(&b[0..1] == "1")),
-            Token::Char(c) => Ok(Literal::Char(c.chars().nth(1).unwrap())),
-            Token::Str(s) => Ok(Literal::CharArray(s.to_string())),
+    pub fn parser<'a>(
+        expr: Parser!['a, Spanned<Expr>],
+        limit: Parser!['a, Spanned<Expr>],
+    ) -> Parser!['a, Self] {
+        filter_map(|span: Span, tok| match tok {
+            Token::Int(i) => Ok(parse_int(i, span)),
+            Token::BigInt(i) => Ok(parse_bigint(i, span)),
+            Token::Float(f) => Ok(parse_float(f, span)),
+            Token::Bit(b) => Ok(Ok(Literal::Bit(&b[0..1] == "1"))),
+            Token::Char(c) => Ok(unescape(c, span.start).and_then(|decoded| {
+                let mut chars = decoded.chars();
+                match (chars.next(), chars.next()) {
+                    (Some(c), None) => Ok(Literal::Char(c)),
+                    _ => Err(Simple::custom(span, "A char literal must contain exactly one char")),
+                }
+            })),
+            Token::Str(s) => Ok(unescape(s, span.start).map(Literal::CharArray)),
             _ => Err(Simple::expected_input_found(
                 span,
                 [
                     Some(Token::Int("...")),
+                    Some(Token::BigInt("...")),
                     Some(Token::Float("...")),
                     Some(Token::Bit("...")),
                     Some(Token::Char("...")),
@@ -195,14 +629,24 @@ impl Literal {
                 Some(tok),
             )),
         })
+        // A literal which can't be decoded or doesn't fit its type is still a literal, so report
+        // the problem without failing the parse. Otherwise the error would be lost to the other
+        // alternatives.
+        .validate(|lit, _, emit| {
+            lit.unwrap_or_else(|err| {
+                emit(err);
+                Literal::CharArray(String::new())
+            })
+        })
         .or(expr
             .clone()
             .separated_by(just(Token::Comma))
             .delimited_by(just(Token::OpenBracket), just(Token::CloseBracket))
             .map(Literal::Array))
         .or(just(Token::Fn)
-            .ignore_then(just(Token::Colon))
-            .ignore_then(Type::parser())
+            .ignore_then(generics())
+            .then_ignore(just(Token::Colon))
+            .then(Type::parser().map_with_span(Spanned::new))
             .then(
                 FnArg::parser()
                     .separated_by(just(Token::Comma))
@@ -210,30 +654,36 @@ impl Literal {
             )
             .then_ignore(just(Token::Arrow))
             .then(
-                expr.clone()
+                limit
+                    .clone()
                     .delimited_by(just(Token::Pipe), just(Token::Pipe)),
             )
             .then(
-                Stmt::parser(expr)
+                Stmt::parser(expr, limit)
                     .repeated()
                     .delimited_by(just(Token::OpenBracket), just(Token::CloseBracket)),
             )
-            .map(|(((ret, args), limit), stmts)| {
+            .map_with_span(|((((generics, ret), args), limit), stmts), span| {
                 Literal::Fn(FnDef {
                     name: Ident(String::from("<closure>")),
+                    generics,
                     ret,
                     args,
                     limit: Box::new(limit),
                     stmts,
+                    span,
                 })
             }))
     }
 }
 
 impl Stmt {
-    pub fn parser<'a>(expr: Parser!['a, Expr]) -> Parser!['a, Self] {
+    pub fn parser<'a>(
+        expr: Parser!['a, Spanned<Expr>],
+        limit: Parser!['a, Spanned<Expr>],
+    ) -> Parser!['a, Spanned<Self>] {
         recursive(|stmt| {
-            FnDef::parser(expr.clone(), stmt.clone())
+            FnDef::parser(limit, stmt.clone())
                 .map(Stmt::FnDef)
                 .or(Assign::parser(expr.clone()).map(Stmt::Assign))
                 .or(just(Token::Sync)
@@ -252,10 +702,26 @@ impl Stmt {
                 .or(just(Token::Type)
                     .ignore_then(Ident::parser())
                     .then_ignore(just(Token::Eq))
-                    .then(Type::parser())
+                    .then(
+                        record_fields(Type::parser())
+                            .map(TypeDefBody::Record)
+                            .or(union_variants().map(TypeDefBody::Union))
+                            .or(Type::parser().map(TypeDefBody::Alias))
+                            .map_with_span(Spanned::new),
+                    )
                     .then_ignore(just(Token::SemiColon))
-                    .map(|(name, ty)| Stmt::TypeDef(name, ty)))
+                    .map(|(name, body)| {
+                        // Records and unions are named after the statement declaring them
+                        let decl = || Decl::new(name.clone());
+                        let ty = match body.node {
+                            TypeDefBody::Record(fields) => Type::Record(decl(), fields),
+                            TypeDefBody::Union(variants) => Type::Union(decl(), variants),
+                            TypeDefBody::Alias(ty) => ty,
+                        };
+                        Stmt::TypeDef(name, Spanned::new(ty, body.span))
+                    }))
                 .or(expr.map(Stmt::Expr).then_ignore(just(Token::SemiColon)))
+                .map_with_span(Spanned::new)
         })
     }
 }
@@ -288,7 +754,7 @@ impl UnOp {
 }
 
 impl WriteTy {
-    pub fn parser<'a>(expr: Parser!['a, Expr]) -> Parser!['a, Self] {
+    pub fn parser<'a>(expr: Parser!['a, Spanned<Expr>]) -> Parser!['a, Self] {
         just(Token::Ident("console"))
             .to(WriteTy::Console)
             .or(just(Token::Ident("error")).to(WriteTy::Error))
@@ -296,3 +762,20 @@ impl WriteTy {
             .or(expr.map(|expr| WriteTy::Other(Box::new(expr))))
     }
 }
+
+impl ReadTy {
+    pub fn parser<'a>(expr: Parser!['a, Spanned<Expr>]) -> Parser!['a, Self] {
+        just(Token::Ident("console"))
+            .to(ReadTy::Console)
+            .or(expr.map(|expr| ReadTy::Other(Box::new(expr))))
+    }
+}
+
+impl ReadAmount {
+    pub fn parser<'a>(expr: Parser!['a, Spanned<Expr>]) -> Parser!['a, Self] {
+        just(Token::Ident("line"))
+            .to(ReadAmount::Line)
+            .or(just(Token::Ident("all")).to(ReadAmount::All))
+            .or(expr.map(|expr| ReadAmount::Count(Box::new(expr))))
+    }
+}
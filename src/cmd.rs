@@ -1,23 +1,73 @@
-use clap::{ArgEnum, Parser};
+use clap::{ArgEnum, Parser, Subcommand};
 use std::path::PathBuf;
 
 #[derive(Copy, Clone, Debug, PartialEq, Eq, ArgEnum)]
 pub enum Emit {
     Tokens,
     Ast,
+    Bytecode,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum Action {
+    /// Start an interactive session
+    Repl,
+    /// Check a file for type errors without running it
+    Check {
+        #[clap(parse(from_os_str))]
+        file: PathBuf,
+    },
 }
 
 #[derive(Debug, Parser)]
-#[clap(version, about, long_about = None)]
+#[clap(version, about, long_about = None, args_conflicts_with_subcommands = true)]
 pub struct Command {
+    #[clap(subcommand)]
+    pub(crate) action: Option<Action>,
     #[clap(parse(from_os_str))]
-    pub(crate) file: PathBuf,
-    #[clap(long, arg_enum, value_delimiter = ',', value_name = "EMIT")]
+    pub(crate) file: Option<PathBuf>,
+    #[clap(long, arg_enum, value_delimiter = ',', value_name = "EMIT", global = true)]
     emit: Vec<Emit>,
+    /// Raise an exception when a float operation produces NaN
+    #[clap(long, global = true)]
+    strict_nan: bool,
+    /// Where `write(honk, ...)` sends its output, which is emptied when goose starts. Without it,
+    /// output goes to `honk`.
+    #[clap(long, global = true, env = "GOOSE_HONK", value_name = "PATH", parse(from_os_str))]
+    honk: Option<PathBuf>,
+    /// A directory to look for imports in, when they aren't next to the importing file
+    #[clap(
+        long = "path",
+        global = true,
+        env = "GOOSE_PATH",
+        value_name = "DIR",
+        value_delimiter = ':',
+        parse(from_os_str)
+    )]
+    search_path: Vec<PathBuf>,
+    /// Arguments for the script, given after `--`, which it sees as `args`
+    #[clap(last = true, value_name = "ARGS")]
+    script_args: Vec<String>,
 }
 
 impl Command {
-    pub fn should_emit(&self, e: Emit) -> bool {
-        self.emit.contains(&e)
+    pub fn emit(&self) -> &[Emit] {
+        &self.emit
+    }
+
+    pub fn strict_nan(&self) -> bool {
+        self.strict_nan
+    }
+
+    pub fn honk(&self) -> Option<&PathBuf> {
+        self.honk.as_ref()
+    }
+
+    pub fn search_path(&self) -> &[PathBuf] {
+        &self.search_path
+    }
+
+    pub fn script_args(&self) -> &[String] {
+        &self.script_args
     }
 }
@@ -0,0 +1,211 @@
+use core::fmt;
+use std::io;
+
+use ariadne::{Color, Config, IndexType, Label, Report, ReportKind};
+
+use crate::ast::Span;
+
+/// Identifies a source registered with [`Sources`]
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct SourceId(u32);
+
+/// A span within a particular source
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Location {
+    pub source: SourceId,
+    pub span: Span,
+}
+
+impl Location {
+    pub fn new(source: SourceId, span: Span) -> Location {
+        Location { source, span }
+    }
+}
+
+/// The names and text of every source an interpreter has seen, so locations can be rendered
+/// long after the code they point into was parsed
+#[derive(Debug, Default)]
+pub struct Sources {
+    files: Vec<(String, String)>,
+}
+
+impl Sources {
+    pub fn add(&mut self, name: &str, text: &str) -> SourceId {
+        self.files.push((name.to_string(), text.to_string()));
+        SourceId(self.files.len() as u32 - 1)
+    }
+
+    pub fn name(&self, id: SourceId) -> &str {
+        &self.files[id.0 as usize].0
+    }
+
+    pub fn text(&self, id: SourceId) -> &str {
+        &self.files[id.0 as usize].1
+    }
+
+    /// The one-based line and column of a byte offset, as shown to users
+    pub fn line_col(&self, id: SourceId, offset: usize) -> (usize, usize) {
+        let before = &self.text(id)[..offset.min(self.text(id).len())];
+        let line = before.matches('\n').count() + 1;
+        let col = before.len() - before.rfind('\n').map_or(0, |idx| idx + 1) + 1;
+        (line, col)
+    }
+
+    /// Format a location as `name:line:col`
+    pub fn describe(&self, location: &Location) -> String {
+        let (line, col) = self.line_col(location.source, location.span.start);
+        format!("{}:{}:{}", self.name(location.source), line, col)
+    }
+
+    pub fn len(&self) -> usize {
+        self.files.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.files.is_empty()
+    }
+}
+
+/// A message about the program, pointing at the locations it concerns
+#[derive(Clone, Debug)]
+pub struct Diagnostic {
+    kind: &'static str,
+    message: String,
+    location: Option<Location>,
+    labels: Vec<(Location, String)>,
+    notes: Vec<String>,
+    trace: Vec<(String, Option<Location>)>,
+}
+
+impl Diagnostic {
+    pub fn new(kind: &'static str, message: String) -> Diagnostic {
+        Diagnostic {
+            kind,
+            message,
+            location: None,
+            labels: Vec::new(),
+            notes: Vec::new(),
+            trace: Vec::new(),
+        }
+    }
+
+    pub fn kind(&self) -> &'static str {
+        self.kind
+    }
+
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+
+    pub fn location(&self) -> Option<&Location> {
+        self.location.as_ref()
+    }
+
+    pub fn with_location(mut self, location: Option<Location>) -> Diagnostic {
+        self.location = location;
+        self
+    }
+
+    pub fn with_label(mut self, location: Location, message: String) -> Diagnostic {
+        self.labels.push((location, message));
+        self
+    }
+
+    pub fn with_note(mut self, note: String) -> Diagnostic {
+        self.notes.push(note);
+        self
+    }
+
+    /// Add a line to the traceback, describing a call and where it was made from. The innermost
+    /// call comes first.
+    pub fn with_frame(mut self, description: String, call_site: Option<Location>) -> Diagnostic {
+        self.trace.push((description, call_site));
+        self
+    }
+
+    /// Render this diagnostic, with the source lines it refers to underlined. Diagnostics without
+    /// a location are rendered as a single line.
+    pub fn write<W: io::Write>(&self, sources: &Sources, color: bool, mut w: W) -> io::Result<()> {
+        match &self.location {
+            Some(location) => self.write_report(location, sources, color, &mut w)?,
+            None => {
+                writeln!(w, "{}: {}", self.kind, self.message)?;
+                for note in &self.notes {
+                    writeln!(w, "{}", note)?;
+                }
+            }
+        }
+
+        if !self.trace.is_empty() {
+            writeln!(w, "Traceback (innermost call first):")?;
+            for (description, call_site) in &self.trace {
+                match call_site {
+                    Some(site) => writeln!(w, "  {}, called at {}", description, sources.describe(site))?,
+                    None => writeln!(w, "  {}", description)?,
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn write_report<W: io::Write>(
+        &self,
+        location: &Location,
+        sources: &Sources,
+        color: bool,
+        mut w: W,
+    ) -> io::Result<()> {
+        let span = |loc: &Location| (sources.name(loc.source).to_string(), loc.span.clone());
+        // Labels given a color keep it whatever the config says, so only give them one when
+        // color is wanted
+        let label = |loc: &Location, message: &str, label_color: Color| {
+            let label = Label::new(span(loc)).with_message(message);
+            if color {
+                label.with_color(label_color)
+            } else {
+                label
+            }
+        };
+
+        let mut report = Report::build(ReportKind::Custom(self.kind, Color::Red), span(location))
+            .with_config(
+                Config::default()
+                    .with_color(color)
+                    .with_index_type(IndexType::Byte),
+            )
+            .with_message(&self.message)
+            .with_label(label(location, &self.message, Color::Red));
+
+        for (loc, message) in &self.labels {
+            report = report.with_label(label(loc, message, Color::Blue));
+        }
+        for note in &self.notes {
+            report = report.with_note(note);
+        }
+
+        let cache = ariadne::sources(
+            (0..sources.len()).map(|idx| {
+                let id = SourceId(idx as u32);
+                (sources.name(id).to_string(), sources.text(id).to_string())
+            }),
+        );
+
+        if color {
+            return report.finish().write(cache, w);
+        }
+
+        // The config doesn't reach the header of a custom report kind, which is always colored, so
+        // it's written out here instead
+        let mut out = Vec::new();
+        report.finish().write(cache, &mut out)?;
+        let body = out.iter().position(|&b| b == b'\n').map_or(out.len(), |idx| idx + 1);
+        writeln!(w, "{}: {}", self.kind, self.message)?;
+        w.write_all(&out[body..])
+    }
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.kind, self.message)
+    }
+}
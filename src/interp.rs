@@ -1,10 +1,12 @@
+use core::cell::RefCell;
 use core::fmt;
 use std::collections::HashMap;
 use std::io;
 use std::ptr::NonNull;
-use typed_arena::Arena;
+use std::rc::Rc;
 
 mod array;
+mod bigint;
 mod null;
 mod func;
 mod int;
@@ -13,32 +15,74 @@ mod bit;
 mod float;
 mod char;
 mod char_array;
+mod file;
+mod honk;
+mod module;
+mod prelude;
+mod record;
+mod union;
 
 pub use array::Array;
+pub use bigint::BigInt;
 pub use int::Int;
 pub use char_array::CharArray;
 pub use bit::Bit;
 pub use func::Fn;
 pub use self::char::Char;
 pub use float::Float;
+pub use file::File;
+pub use honk::Honk;
+pub use module::Module;
+pub use record::Record;
+pub use union::Union;
 pub use ty::Type;
 
 use crate::ast::{BinOp, Ident, UnOp};
+use crate::diagnostic::{Diagnostic, Location};
 
 pub type Result<T> = core::result::Result<T, Exception>;
 
 #[derive(Debug)]
-pub enum Exception {
+pub enum ExceptionKind {
     InvalidType(Type, Type),
     InvalidOp(Op, Type, Option<Type>),
     NameNotFound(Ident),
+    InvalidArgCount(Ident, usize, usize),
+    DivideByZero,
+    Overflow(Op),
+    /// A float came out as NaN under strict NaN mode, naming what produced it
+    NotANumber(String),
+    OutOfRange(Type),
+    InvalidConversion(Type, Type),
+    ParseFailed(String, Type),
+    FieldNotFound(Type, Ident),
+    ReadOnlyField(Type, Ident),
+    MissingField(Type, Ident),
+    NotARecord(Type),
+    NotAVariant(Ident),
+    WrongVariant(Ident, Ident),
+    NotIndexable(Type),
+    IndexOutOfRange(i128, usize),
+    EmptyArray(String),
+    InvalidReadAmount(String),
+    InvalidOpenMode(String),
+    InvalidFd(i128),
+    FileClosed(String),
+    EnvVarNotFound(String),
+    InvalidEnvVar(String),
+    InvalidExitStatus(i128),
+    ImportNotFound(String),
+    ImportCycle(String),
+    NotExported(String, Ident),
+    /// Raised by `exit`, to unwind the whole program
+    Exit(u8),
     Io,
 }
 
-impl fmt::Display for Exception {
+impl fmt::Display for ExceptionKind {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
-            Exception::InvalidType(expected, actual) => {
+            ExceptionKind::InvalidType(expected, actual) => {
                 write!(
                     f,
                     "Expected type `{}`, got type `{}`",
@@ -46,7 +90,7 @@ impl fmt::Display for Exception {
                     actual.pretty()
                 )
             }
-            Exception::InvalidOp(op, left, Some(right)) => {
+            ExceptionKind::InvalidOp(op, left, Some(right)) => {
                 write!(
                     f,
                     "Attempted to invoke binary operator {} on invalid types. Left: `{}`, Right: `{}`",
@@ -55,7 +99,7 @@ impl fmt::Display for Exception {
                     right.pretty(),
                 )
             }
-            Exception::InvalidOp(op, right, None) => {
+            ExceptionKind::InvalidOp(op, right, None) => {
                 write!(
                     f,
                     "Attempted to invoke unary operator {} on invalid type `{}`",
@@ -63,34 +107,256 @@ impl fmt::Display for Exception {
                     right.pretty(),
                 )
             }
-            Exception::NameNotFound(name) => {
+            ExceptionKind::NameNotFound(name) => {
                 write!(f, "Attempted to access invalid identifier {}", &**name)
             }
-            Exception::Io => {
+            ExceptionKind::InvalidArgCount(name, expected, actual) => {
+                write!(
+                    f,
+                    "Function `{}` expects {} arguments, got {}",
+                    &**name,
+                    expected,
+                    actual,
+                )
+            }
+            ExceptionKind::DivideByZero => {
+                write!(f, "Attempted to divide by zero")
+            }
+            ExceptionKind::Overflow(op) => {
+                write!(f, "Integer overflow in operator {}", op.pretty())
+            }
+            ExceptionKind::NotANumber(source) => {
+                write!(f, "{} produced NaN", source)
+            }
+            ExceptionKind::OutOfRange(ty) => {
+                write!(f, "Value is out of range for type `{}`", ty.pretty())
+            }
+            ExceptionKind::InvalidConversion(from, to) => {
+                write!(f, "Cannot convert type `{}` to type `{}`", from.pretty(), to.pretty())
+            }
+            ExceptionKind::ParseFailed(text, ty) => {
+                write!(f, "Could not parse {:?} as type `{}`", text, ty.pretty())
+            }
+            ExceptionKind::FieldNotFound(ty, name) => {
+                write!(f, "Type `{}` has no field `{}`", ty.pretty(), &**name)
+            }
+            ExceptionKind::ReadOnlyField(ty, name) => {
+                write!(f, "Field `{}` of type `{}` can't be assigned", &**name, ty.pretty())
+            }
+            ExceptionKind::MissingField(ty, name) => {
+                write!(f, "Type `{}` is missing field `{}`", ty.pretty(), &**name)
+            }
+            ExceptionKind::NotARecord(ty) => {
+                write!(f, "Type `{}` is not a record", ty.pretty())
+            }
+            ExceptionKind::NotAVariant(name) => {
+                write!(f, "Function `{}` is not a variant constructor", &**name)
+            }
+            ExceptionKind::WrongVariant(expected, actual) => {
+                write!(f, "Expected variant `{}`, got variant `{}`", &**expected, &**actual)
+            }
+            ExceptionKind::NotIndexable(ty) => {
+                write!(f, "Attempted to index into invalid type `{}`", ty.pretty())
+            }
+            ExceptionKind::IndexOutOfRange(idx, len) => {
+                write!(f, "Index {} is out of range for length {}", idx, len)
+            }
+            ExceptionKind::EmptyArray(name) => {
+                write!(f, "`{}` needs an array with at least one element, got an empty one", name)
+            }
+            ExceptionKind::InvalidReadAmount(amount) => {
+                write!(f, "Expected a count, `line` or `all` to read, got {}", amount)
+            }
+            ExceptionKind::InvalidOpenMode(mode) => {
+                write!(
+                    f,
+                    "Expected a mode of `read`, `write`, `append` or `truncate`, got {:?}",
+                    mode,
+                )
+            }
+            ExceptionKind::InvalidFd(fd) => {
+                write!(f, "{} is not an open file descriptor", fd)
+            }
+            ExceptionKind::FileClosed(name) => {
+                write!(f, "Attempted to use {} after it was closed", name)
+            }
+            ExceptionKind::EnvVarNotFound(name) => {
+                write!(f, "Environment variable `{}` is not set", name)
+            }
+            ExceptionKind::InvalidEnvVar(name) => {
+                write!(f, "Environment variable {:?} can't be set to that value", name)
+            }
+            ExceptionKind::InvalidExitStatus(status) => {
+                write!(f, "Exit status {} is outside 0 to 255", status)
+            }
+            ExceptionKind::ImportNotFound(path) => {
+                write!(f, "Couldn't find `{}` to import", path)
+            }
+            ExceptionKind::ImportCycle(path) => {
+                write!(f, "Import cycle: `{}` ends up importing itself", path)
+            }
+            ExceptionKind::NotExported(path, name) => {
+                write!(f, "Module `{}` has no definition `{}`", path, &**name)
+            }
+            ExceptionKind::Exit(status) => {
+                write!(f, "Exited with status {}", status)
+            }
+            ExceptionKind::Io => {
                 write!(f, "IO operation failed")
             }
         }
     }
 }
 
-impl From<io::Error> for Exception {
+impl From<io::Error> for ExceptionKind {
     fn from(_: io::Error) -> Self {
-        Exception::Io
+        ExceptionKind::Io
+    }
+}
+
+/// A function call an exception propagated out of
+#[derive(Clone, Debug)]
+pub struct TraceFrame {
+    name: String,
+    call_site: Option<Location>,
+    iteration: usize,
+}
+
+impl TraceFrame {
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Where the function was called from, if it was called by goose code
+    pub fn call_site(&self) -> Option<&Location> {
+        self.call_site.as_ref()
+    }
+
+    /// The iteration the function was on when the exception left it
+    pub fn iteration(&self) -> usize {
+        self.iteration
+    }
+}
+
+/// An error raised while running goose code, along with where it happened
+#[derive(Debug)]
+pub struct Exception {
+    kind: Box<ExceptionKind>,
+    location: Option<Location>,
+    labels: Vec<(Location, String)>,
+    trace: Vec<TraceFrame>,
+}
+
+impl Exception {
+    pub fn kind(&self) -> &ExceptionKind {
+        &self.kind
+    }
+
+    pub fn location(&self) -> Option<&Location> {
+        self.location.as_ref()
+    }
+
+    pub fn labels(&self) -> &[(Location, String)] {
+        &self.labels
+    }
+
+    /// The goose calls this exception propagated out of, innermost first
+    pub fn trace(&self) -> &[TraceFrame] {
+        &self.trace
+    }
+
+    /// Set where this exception was raised, if that isn't already known. Exceptions keep the
+    /// innermost location they pass through.
+    pub fn at(mut self, location: Location) -> Exception {
+        if self.location.is_none() {
+            self.location = Some(location);
+        }
+        self
+    }
+
+    /// Attach a secondary location, explaining how the exception came about
+    pub fn with_label(mut self, location: Location, message: String) -> Exception {
+        self.labels.push((location, message));
+        self
+    }
+
+    /// Record that this exception propagated out of a function call
+    pub(crate) fn in_frame(mut self, frame: &Frame) -> Exception {
+        self.trace.push(TraceFrame {
+            name: frame.name.clone(),
+            call_site: None,
+            iteration: frame.iteration,
+        });
+        self
+    }
+
+    /// Record where the call this exception last propagated out of was made
+    pub(crate) fn called_from(mut self, location: Location) -> Exception {
+        if let Some(frame) = self.trace.last_mut() {
+            frame.call_site.get_or_insert(location);
+        }
+        self
+    }
+
+    pub fn diagnostic(&self) -> Diagnostic {
+        let diag = self.labels.iter().fold(
+            Diagnostic::new("Exception", self.kind.to_string()).with_location(self.location.clone()),
+            |diag, (loc, msg)| diag.with_label(loc.clone(), msg.clone()),
+        );
+        self.trace.iter().fold(diag, |diag, frame| {
+            diag.with_frame(
+                format!("in `{}` on iteration {}", frame.name, frame.iteration),
+                frame.call_site.clone(),
+            )
+        })
     }
 }
 
-#[derive(Copy, Clone, Debug)]
+impl fmt::Display for Exception {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.kind)
+    }
+}
+
+impl From<ExceptionKind> for Exception {
+    fn from(kind: ExceptionKind) -> Self {
+        Exception {
+            kind: Box::new(kind),
+            location: None,
+            labels: Vec::new(),
+            trace: Vec::new(),
+        }
+    }
+}
+
+impl From<io::Error> for Exception {
+    fn from(e: io::Error) -> Self {
+        ExceptionKind::from(e).into()
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
 pub enum Op {
     Inv,
     Neg,
 
     Eq,
     Neq,
+    Lt,
+    Gt,
+    LtEq,
+    GtEq,
 
     Add,
     Sub,
     Mul,
     Div,
+
+    BitAnd,
+    BitOr,
+    BitXor,
+    And,
+    Or,
 }
 
 impl Op {
@@ -100,10 +366,19 @@ impl Op {
             Op::Neg => String::from("-"),
             Op::Eq => String::from("=="),
             Op::Neq => String::from("!="),
+            Op::Lt => String::from("<"),
+            Op::Gt => String::from(">"),
+            Op::LtEq => String::from("<="),
+            Op::GtEq => String::from(">="),
             Op::Add => String::from("+"),
             Op::Sub => String::from("-"),
             Op::Mul => String::from("*"),
             Op::Div => String::from("/"),
+            Op::BitAnd => String::from("&"),
+            Op::BitOr => String::from("|"),
+            Op::BitXor => String::from("^"),
+            Op::And => String::from("&&"),
+            Op::Or => String::from("||"),
         }
     }
 }
@@ -122,10 +397,19 @@ impl From<BinOp> for Op {
         match op {
             BinOp::Eq => Op::Eq,
             BinOp::Neq => Op::Neq,
+            BinOp::Lt => Op::Lt,
+            BinOp::Gt => Op::Gt,
+            BinOp::LtEq => Op::LtEq,
+            BinOp::GtEq => Op::GtEq,
             BinOp::Add => Op::Add,
             BinOp::Sub => Op::Sub,
             BinOp::Mul => Op::Mul,
             BinOp::Div => Op::Div,
+            BinOp::BitAnd => Op::BitAnd,
+            BinOp::BitOr => Op::BitOr,
+            BinOp::BitXor => Op::BitXor,
+            BinOp::And => Op::And,
+            BinOp::Or => Op::Or,
         }
     }
 }
@@ -135,6 +419,7 @@ pub struct BuiltinFn {
     name: Ident,
     ret: Type,
     args: Vec<Type>,
+    variadic: bool,
     handler: for<'ip> fn(env: &mut Env<'ip>, args: &[Value<'ip>]) -> Result<Value<'ip>>,
 }
 
@@ -159,78 +444,350 @@ impl BuiltinFn {
             name: Ident(String::from(name)),
             ret,
             args,
+            variadic: false,
             handler,
         }
     }
 
+    /// A builtin which accepts any number of arguments, of any type
+    pub fn variadic(
+        name: &str,
+        ret: Type,
+        handler: for<'ip> fn(env: &mut Env<'ip>, args: &[Value<'ip>]) -> Result<Value<'ip>>,
+    ) -> BuiltinFn {
+        BuiltinFn {
+            variadic: true,
+            ..BuiltinFn::new(name, ret, vec![], handler)
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn is_variadic(&self) -> bool {
+        self.variadic
+    }
+
     pub fn invoke<'ip>(&self, env: &mut Env<'ip>, args: &[Value<'ip>]) -> Result<Value<'ip>> {
+        if !self.variadic && args.len() != self.args.len() {
+            let kind = ExceptionKind::InvalidArgCount(self.name.clone(), self.args.len(), args.len());
+            return Err(kind.into());
+        }
+        if !self.variadic {
+            for (expected, arg) in self.args.iter().zip(args) {
+                if !expected.matches(&arg.ty()) {
+                    return Err(ExceptionKind::InvalidType(Clone::clone(expected), arg.ty()).into());
+                }
+            }
+        }
         (self.handler)(env, args)
     }
 }
 
-#[derive(Debug, Default)]
-pub struct Env<'ip> {
-    tys: Arena<Type>,
+/// A single active function call
+#[derive(Clone, Debug)]
+pub struct Frame {
+    name: String,
+    iteration: usize,
     sync: bool,
-    first_iter: bool,
-    value_stack: Vec<HashMap<String, Value<'ip>>>,
+}
+
+impl Frame {
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// How many times the function has looped, starting at zero
+    pub fn iteration(&self) -> usize {
+        self.iteration
+    }
+}
+
+#[derive(Debug)]
+struct Var<'ip> {
+    value: Value<'ip>,
+    /// The type of the variable, which stays put when it's given an empty array
+    ty: Type,
+    defined: Option<Location>,
+}
+
+struct ScopeData<'ip> {
+    vars: HashMap<String, Var<'ip>>,
+    parent: Option<Scope<'ip>>,
+}
+
+/// A set of variables, along with the scope it's nested in. Scopes are shared, so a closure can
+/// keep the scope it was created in alive after the call that created it has returned.
+#[derive(Clone)]
+pub struct Scope<'ip>(Rc<RefCell<ScopeData<'ip>>>);
+
+impl<'ip> Scope<'ip> {
+    fn new(parent: Option<Scope<'ip>>) -> Scope<'ip> {
+        Scope(Rc::new(RefCell::new(ScopeData {
+            vars: HashMap::new(),
+            parent,
+        })))
+    }
+
+    fn parent(&self) -> Option<Scope<'ip>> {
+        self.0.borrow().parent.clone()
+    }
+
+    /// Find the innermost definition of a variable, and apply `f` to it
+    fn find<R>(&self, name: &str, f: impl FnOnce(&mut Var<'ip>) -> R) -> Option<R> {
+        let mut scope = self.clone();
+        loop {
+            if let Some(var) = scope.0.borrow_mut().vars.get_mut(name) {
+                return Some(f(var));
+            }
+            scope = scope.parent()?;
+        }
+    }
+
+    fn insert(&self, name: &str, value: Value<'ip>, location: Option<Location>) {
+        let mut data = self.0.borrow_mut();
+        let ty = value.ty();
+        let var = data.vars.entry(name.to_string()).or_insert(Var {
+            value: Value::null(),
+            ty: Clone::clone(&ty),
+            defined: location,
+        });
+        var.ty = ty;
+        var.value = value;
+    }
+
+    /// The variables defined directly in this scope, rather than the scopes it's nested in
+    pub fn vars(&self) -> Vec<(String, Value<'ip>)> {
+        self.0
+            .borrow()
+            .vars
+            .iter()
+            .map(|(name, var)| (name.clone(), var.value.clone()))
+            .collect()
+    }
+
+    pub fn ptr_eq(&self, other: &Scope<'ip>) -> bool {
+        Rc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+impl fmt::Debug for Scope<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // Scopes can contain closures which refer back to them, so only show the names
+        f.debug_set().entries(self.0.borrow().vars.keys()).finish()
+    }
+}
+
+#[derive(Debug)]
+pub struct Env<'ip> {
+    frames: Vec<Frame>,
+    /// Whether float operators producing NaN raise an exception
+    strict_nan: bool,
+    honk: Honk,
+    globals: Scope<'ip>,
+    /// The innermost scope of the running code
+    scope: Scope<'ip>,
+}
+
+impl Default for Env<'_> {
+    fn default() -> Self {
+        Env::new()
+    }
 }
 
 impl<'ip> Env<'ip> {
-    pub fn set_sync(&mut self, sync: bool) {
-        self.sync = sync;
+    /// Create a new environment, with the global scope and builtins already present
+    pub fn new() -> Env<'ip> {
+        let globals = Scope::new(None);
+        let mut env = Env {
+            frames: Vec::new(),
+            strict_nan: false,
+            honk: Honk::default(),
+            scope: globals.clone(),
+            globals,
+        };
+        prelude::install(&mut env);
+        env
+    }
+
+    pub fn push_frame(&mut self, name: &str) {
+        self.frames.push(Frame {
+            name: name.to_string(),
+            iteration: 0,
+            sync: false,
+        });
+    }
+
+    pub fn pop_frame(&mut self) {
+        self.frames.pop();
+    }
+
+    pub fn frames(&self) -> &[Frame] {
+        &self.frames
     }
 
-    pub fn set_first_iter(&mut self, first: bool) {
-        self.first_iter = first;
+    /// Move the current frame on to its next iteration
+    pub fn next_iter(&mut self) {
+        if let Some(frame) = self.frames.last_mut() {
+            frame.iteration += 1;
+        }
     }
 
+    /// Whether the current frame is on its first iteration. Code outside any function only runs
+    /// once, so it's always on its first iteration.
     pub fn is_first_iter(&self) -> bool {
-        self.first_iter
+        self.frames.last().is_none_or(|frame| frame.iteration == 0)
+    }
+
+    pub fn set_sync(&mut self, sync: bool) {
+        if let Some(frame) = self.frames.last_mut() {
+            frame.sync = sync;
+        }
+    }
+
+    pub fn is_sync(&self) -> bool {
+        self.frames.last().is_some_and(|frame| frame.sync)
+    }
+
+    /// Make float operators raise an exception when they produce NaN, instead of following IEEE 754
+    pub fn set_strict_nan(&mut self, strict: bool) {
+        self.strict_nan = strict;
+    }
+
+    pub fn is_strict_nan(&self) -> bool {
+        self.strict_nan
+    }
+
+    /// Where `write(honk, ...)` goes
+    pub fn honk(&mut self) -> &mut Honk {
+        &mut self.honk
+    }
+
+    /// The innermost scope of the running code, which closures created now capture
+    pub fn scope(&self) -> &Scope<'ip> {
+        &self.scope
+    }
+
+    /// Run in a new scope nested in `parent`, rather than the current one, returning the scope
+    /// to go back to with [`Env::restore_scope`]. This is how a function sees the variables
+    /// around its definition, rather than those of its caller.
+    pub fn enter_scope(&mut self, parent: &Scope<'ip>) -> Scope<'ip> {
+        std::mem::replace(&mut self.scope, Scope::new(Some(parent.clone())))
+    }
+
+    /// Run in a new scope nested in the global one, as an imported file does, returning the scope
+    /// to go back to with [`Env::restore_scope`]
+    pub fn enter_module(&mut self) -> Scope<'ip> {
+        let globals = self.globals.clone();
+        self.enter_scope(&globals)
+    }
+
+    pub fn restore_scope(&mut self, scope: Scope<'ip>) {
+        self.scope = scope;
     }
 
     pub fn push_scope(&mut self) {
-        self.value_stack.push(HashMap::new());
+        self.scope = Scope::new(Some(self.scope.clone()));
     }
 
-    pub fn lookup_var(&mut self, var: &str) -> Option<&Value<'ip>> {
-        let scope = self
-            .value_stack
-            .iter()
-            .rev()
-            .find(|scope| scope.contains_key(var));
+    pub fn lookup_var(&self, var: &str) -> Option<Value<'ip>> {
+        self.scope.find(var, |var| var.value.clone())
+    }
+
+    /// Where a variable was first defined, if it was defined by goose code
+    pub fn lookup_location(&self, var: &str) -> Option<Location> {
+        self.scope.find(var, |var| var.defined.clone()).flatten()
+    }
+
+    /// Define a variable in the innermost scope
+    pub fn insert_var(&mut self, name: &str, value: Value<'ip>) {
+        self.scope.insert(name, value, None);
+    }
 
-        scope.and_then(|scope| scope.get(var))
+    /// Define a variable in the innermost scope, remembering the location it was defined at if it
+    /// didn't already exist there
+    pub fn insert_var_at(&mut self, name: &str, value: Value<'ip>, location: Location) {
+        self.scope.insert(name, value, Some(location));
     }
 
-    pub fn insert_var(&mut self, name: &str, value: Value<'ip>) -> &Value<'ip> {
-        let scope = self.value_stack.last_mut().unwrap();
-        scope.insert(name.to_string(), value);
-        scope.get(name).unwrap()
+    /// The type of a variable, which assigning an empty array to it doesn't make any less specific
+    pub fn lookup_var_ty(&self, var: &str) -> Option<Type> {
+        self.scope.find(var, |var| Clone::clone(&var.ty))
     }
 
+    /// Update an existing variable in the scope it was defined in, returning whether it was found
+    pub fn set_var(&mut self, name: &str, value: Value<'ip>) -> bool {
+        self.scope
+            .find(name, |var| {
+                let ty = value.ty();
+                var.ty = var.ty.join(&ty).unwrap_or(ty);
+                var.value = value;
+            })
+            .is_some()
+    }
+
+    pub fn insert_global(&mut self, name: &str, value: Value<'ip>) {
+        self.globals.insert(name, value, None);
+    }
+
+    /// # Panics
+    ///
+    /// If the global scope is popped
     pub fn pop_scope(&mut self) {
-        self.value_stack.pop();
+        self.scope = self.scope.parent().expect("Attempted to pop the global scope");
+    }
+
+    pub fn scope_depth(&self) -> usize {
+        let mut depth = 1;
+        let mut scope = self.scope.clone();
+        while let Some(parent) = scope.parent() {
+            depth += 1;
+            scope = parent;
+        }
+        depth
     }
 
-    fn new_ty(&mut self, name: &str) -> Type {
-        let new_id = self.tys.len() as u64;
-        Type::new(new_id, name.to_string())
+    /// Discard any scopes above the given depth, such as those left behind by an exception
+    pub fn truncate_scopes(&mut self, depth: usize) {
+        for _ in depth..self.scope_depth() {
+            self.pop_scope();
+        }
     }
 }
 
+/// The behavior of some data stored in a [`Value`]
+///
+/// # Safety
+///
+/// `allow_cast` must only succeed for a type if every value reporting that type from `ty` is an
+/// instance of `Self`, as downcasting relies on it to reinterpret the data.
 pub unsafe trait ValItem<'ip>: 'ip {
     fn allow_cast(ty: Type) -> Result<()>
     where
         Self: Sized;
 
     fn clone(&self) -> Box<dyn ValItem<'ip> + 'ip>;
-    fn ty(&self, env: &mut Env<'_>) -> Type;
+    fn ty(&self) -> Type;
     fn write(&self, w: &mut dyn io::Write) -> io::Result<()> {
         #![allow(unused_variables)]
         Ok(())
     }
     fn get_field(&self, name: &str) -> Option<Value<'ip>>;
+    /// A copy of this value with a field replaced, for values whose fields can be assigned
+    fn set_field(&self, env: &mut Env<'ip>, name: &str, val: Value<'ip>) -> Result<Value<'ip>> {
+        #![allow(unused_variables)]
+        let name = Ident(name.to_string());
+        match self.get_field(&name) {
+            Some(_) => Err(ExceptionKind::ReadOnlyField(self.ty(), name).into()),
+            None => Err(ExceptionKind::FieldNotFound(self.ty(), name).into()),
+        }
+    }
+    /// The element at `idx`, for values which can be indexed with `#`
+    fn get_index(&self, idx: i128) -> Result<Value<'ip>> {
+        #![allow(unused_variables)]
+        Err(ExceptionKind::NotIndexable(self.ty()).into())
+    }
     fn get_op(&self, op: Op) -> Option<Fn<'ip>>;
 }
 
@@ -270,7 +827,7 @@ impl fmt::Debug for Value<'_> {
 }
 
 impl<'ip> Value<'ip> {
-    pub(crate) fn new<T: ValItem<'ip> + 'ip>(item: T) -> Value<'ip> {
+    pub fn new<T: ValItem<'ip> + 'ip>(item: T) -> Value<'ip> {
         Value { data: Box::new(item) }
     }
 
@@ -288,35 +845,23 @@ impl<'ip> Value<'ip> {
 
     pub fn write<W: io::Write>(&self, w: &mut W) -> Result<()> {
         self.data.write(w)?;
-        /*match self {
-            Value::Null => write!(w, "null")?,
-            Value::Int(i) => write!(w, "{}", i)?,
-            Value::Float(f) => write!(w, "{}", f)?,
-            Value::Bit(b) => {
-                if *b {
-                    write!(w, "1b")?
-                } else {
-                    write!(w, "0b")?
-                }
-            }
-            Value::Char(c) => write!(w, "{}", c)?,
-            Value::String(s) => write!(w, "{}", s)?,
-            Value::Type(t) => write!(w, "<type {}>", t.pretty())?,
-            Value::Array(a) => {
-                write!(w, "[")?;
-                for (idx, item) in a.iter().enumerate() {
-                    if idx != 0 {
-                        write!(w, ", ")?;
-                    }
-                    item.write(w)?;
-                }
-                write!(w, "]")?;
-            }
-            Value::Fn(f) => write!(w, "<fn {}>", f.name())?,
-        };*/
         Ok(())
     }
 
+    pub fn get_field(&self, name: &str) -> Result<Value<'ip>> {
+        self.data
+            .get_field(name)
+            .ok_or_else(|| ExceptionKind::FieldNotFound(self.ty(), Ident(name.to_string())).into())
+    }
+
+    pub fn set_field(&self, env: &mut Env<'ip>, name: &str, val: Value<'ip>) -> Result<Value<'ip>> {
+        self.data.set_field(env, name, val)
+    }
+
+    pub fn get_index(&self, idx: i128) -> Result<Value<'ip>> {
+        self.data.get_index(idx)
+    }
+
     pub fn get_op(&self, op: Op) -> Option<Fn<'ip>> {
         // TODO: Handle fallback to inverting Eq/Neq
         self.data.get_op(op)
@@ -1,14 +1,48 @@
-use crate::interp::Env;
-use super::{ValItem, Value, Fn, Result, Exception, Type, Op};
+use std::io::{self, Write};
+use super::{ValItem, Value, Bit, Env, Fn, Result, BuiltinFn, ExceptionKind, Type, Op, Int};
 
 pub struct Array<'ip>(pub(crate) Vec<Value<'ip>>);
 
+impl<'ip> Array<'ip> {
+    /// Compare two arrays element by element, using the elements' own equality
+    fn equals(&self, env: &mut Env<'ip>, other: &Array<'ip>) -> Result<bool> {
+        if self.0.len() != other.0.len() {
+            return Ok(false);
+        }
+
+        for (a, b) in self.0.iter().zip(&other.0) {
+            let eq = a
+                .get_op(Op::Eq)
+                .ok_or_else(|| ExceptionKind::InvalidOp(Op::Eq, a.ty(), Some(b.ty())))?
+                .invoke(env, vec![a.clone(), b.clone()])?;
+            if !eq.downcast::<Bit>()?.val() {
+                return Ok(false);
+            }
+        }
+        Ok(true)
+    }
+
+    /// Whether any element equals `val`, using the elements' own equality
+    pub(crate) fn contains(&self, env: &mut Env<'ip>, val: &Value<'ip>) -> Result<bool> {
+        for elem in &self.0 {
+            let eq = elem
+                .get_op(Op::Eq)
+                .ok_or_else(|| ExceptionKind::InvalidOp(Op::Eq, elem.ty(), Some(val.ty())))?
+                .invoke(env, vec![elem.clone(), val.clone()])?;
+            if eq.downcast::<Bit>()?.val() {
+                return Ok(true);
+            }
+        }
+        Ok(false)
+    }
+}
+
 unsafe impl<'ip> ValItem<'ip> for Array<'ip> {
     fn allow_cast(ty: Type) -> Result<()> {
         if let Type::Array(_) = ty {
             Ok(())
         } else {
-            Err(Exception::InvalidType(Type::Array(Box::new(Type::named("any"))), ty))
+            Err(ExceptionKind::InvalidType(Type::Array(Box::new(Type::named("any"))), ty).into())
         }
     }
 
@@ -16,18 +50,83 @@ unsafe impl<'ip> ValItem<'ip> for Array<'ip> {
         Box::new(Array(self.0.clone()))
     }
 
-    fn ty(&self, env: &mut Env<'_>) -> Type {
-        let inner = self.0.get(0)
-            .map_or_else(|| Type::named("null"), |v| v.ty());
+    fn ty(&self) -> Type {
+        // An empty array has no elements to disagree with, so it can stand in for any array. Only
+        // an element holding empty arrays leaves part of the type open, for later ones to fill in.
+        let mut elems = self.0.iter().map(|v| v.ty());
+        let first = elems.next().unwrap_or_else(|| Type::named("any"));
+        let inner = if first.is_generic() {
+            elems.fold(first, |ty, elem| ty.join(&elem).unwrap_or(ty))
+        } else {
+            first
+        };
 
         Type::Array(Box::new(inner))
     }
 
-    fn get_field(&self, _name: &str) -> Option<Value<'ip>> {
-        None
+    fn write(&self, w: &mut dyn Write) -> io::Result<()> {
+        write!(w, "[")?;
+        for (idx, item) in self.0.iter().enumerate() {
+            if idx != 0 {
+                write!(w, ", ")?;
+            }
+            item.data.write(w)?;
+        }
+        write!(w, "]")
+    }
+
+    fn get_field(&self, name: &str) -> Option<Value<'ip>> {
+        match name {
+            "len" => Some(Value::new(Int::new(self.0.len() as i128))),
+            _ => None,
+        }
+    }
+
+    fn get_index(&self, idx: i128) -> Result<Value<'ip>> {
+        usize::try_from(idx)
+            .ok()
+            .and_then(|i| self.0.get(i))
+            .cloned()
+            .ok_or_else(|| ExceptionKind::IndexOutOfRange(idx, self.0.len()).into())
     }
 
-    fn get_op(&self, _op: Op) -> Option<Fn<'ip>> {
-        todo!()
+    fn get_op(&self, op: Op) -> Option<Fn<'ip>> {
+        let ty = ValItem::ty(self);
+        match op {
+            Op::Eq => Some(BuiltinFn::new(
+                "array_eq",
+                Type::named("bit"),
+                vec![Clone::clone(&ty), ty],
+                |env, args| {
+                    let a = args[0].downcast::<Array<'_>>()?;
+                    let b = args[1].downcast::<Array<'_>>()?;
+
+                    Ok(Value::new(Bit::new(a.equals(env, b)?)))
+                }
+            ).into()),
+            Op::Neq => Some(BuiltinFn::new(
+                "array_neq",
+                Type::named("bit"),
+                vec![Clone::clone(&ty), ty],
+                |env, args| {
+                    let a = args[0].downcast::<Array<'_>>()?;
+                    let b = args[1].downcast::<Array<'_>>()?;
+
+                    Ok(Value::new(Bit::new(!a.equals(env, b)?)))
+                }
+            ).into()),
+            Op::Add => Some(BuiltinFn::new(
+                "array_concat",
+                Clone::clone(&ty),
+                vec![Clone::clone(&ty), ty],
+                |_env, args| {
+                    let a = args[0].downcast::<Array<'_>>()?;
+                    let b = args[1].downcast::<Array<'_>>()?;
+
+                    Ok(Value::new(Array(a.0.iter().chain(&b.0).cloned().collect())))
+                }
+            ).into()),
+            _ => None,
+        }
     }
 }
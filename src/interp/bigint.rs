@@ -0,0 +1,226 @@
+use std::io;
+use std::io::Write;
+use std::ops::Deref;
+use num_traits::Zero;
+use super::{ValItem, Value, Op, Bit, Fn, Result, BuiltinFn, ExceptionKind, Type};
+
+/// An integer without a fixed size, which can't overflow
+#[derive(Clone)]
+pub struct BigInt(num_bigint::BigInt);
+
+impl BigInt {
+    pub fn new(val: num_bigint::BigInt) -> BigInt {
+        BigInt(val)
+    }
+
+    pub fn val(&self) -> &num_bigint::BigInt {
+        &self.0
+    }
+}
+
+unsafe impl<'ip> ValItem<'ip> for BigInt {
+    fn allow_cast(ty: Type) -> Result<()> {
+        if ty == Type::named("bigint") {
+            Ok(())
+        } else {
+            Err(ExceptionKind::InvalidType(Type::named("bigint"), ty).into())
+        }
+    }
+
+    fn clone(&self) -> Box<dyn ValItem<'ip>> {
+        Box::new(BigInt(self.0.clone()))
+    }
+
+    fn ty(&self) -> Type {
+        Type::named("bigint")
+    }
+
+    fn write(&self, w: &mut dyn Write) -> io::Result<()> {
+        write!(w, "{}", self.0)
+    }
+
+    fn get_field(&self, _: &str) -> Option<Value<'ip>> {
+        None
+    }
+
+    fn get_op(&self, op: Op) -> Option<Fn<'ip>> {
+        match op {
+            Op::Eq => Some(BuiltinFn::new(
+                "bigint_eq",
+                Type::named("bit"),
+                vec![Type::named("bigint"), Type::named("bigint")],
+                |_env, args| {
+                    let a = args[0].downcast::<BigInt>()?;
+                    let b = args[1].downcast::<BigInt>()?;
+
+                    Ok(Value::new(Bit::new(a.0 == b.0)))
+                }
+            ).into()),
+            Op::Neq => Some(BuiltinFn::new(
+                "bigint_neq",
+                Type::named("bit"),
+                vec![Type::named("bigint"), Type::named("bigint")],
+                |_env, args| {
+                    let a = args[0].downcast::<BigInt>()?;
+                    let b = args[1].downcast::<BigInt>()?;
+
+                    Ok(Value::new(Bit::new(a.0 != b.0)))
+                }
+            ).into()),
+            Op::Inv => Some(BuiltinFn::new(
+                "bigint_inv",
+                Type::named("bigint"),
+                vec![Type::named("bigint")],
+                |_env, args| {
+                    let a = args[0].downcast::<BigInt>()?;
+
+                    Ok(Value::new(BigInt(!&a.0)))
+                }
+            ).into()),
+            Op::Neg => Some(BuiltinFn::new(
+                "bigint_neg",
+                Type::named("bigint"),
+                vec![Type::named("bigint")],
+                |_env, args| {
+                    let a = args[0].downcast::<BigInt>()?;
+
+                    Ok(Value::new(BigInt(-&a.0)))
+                }
+            ).into()),
+            Op::Add => Some(BuiltinFn::new(
+                "bigint_add",
+                Type::named("bigint"),
+                vec![Type::named("bigint"), Type::named("bigint")],
+                |_env, args| {
+                    let a = args[0].downcast::<BigInt>()?;
+                    let b = args[1].downcast::<BigInt>()?;
+
+                    Ok(Value::new(BigInt(&a.0 + &b.0)))
+                }
+            ).into()),
+            Op::Sub => Some(BuiltinFn::new(
+                "bigint_sub",
+                Type::named("bigint"),
+                vec![Type::named("bigint"), Type::named("bigint")],
+                |_env, args| {
+                    let a = args[0].downcast::<BigInt>()?;
+                    let b = args[1].downcast::<BigInt>()?;
+
+                    Ok(Value::new(BigInt(&a.0 - &b.0)))
+                }
+            ).into()),
+            Op::Mul => Some(BuiltinFn::new(
+                "bigint_mul",
+                Type::named("bigint"),
+                vec![Type::named("bigint"), Type::named("bigint")],
+                |_env, args| {
+                    let a = args[0].downcast::<BigInt>()?;
+                    let b = args[1].downcast::<BigInt>()?;
+
+                    Ok(Value::new(BigInt(&a.0 * &b.0)))
+                }
+            ).into()),
+            Op::Div => Some(BuiltinFn::new(
+                "bigint_div",
+                Type::named("bigint"),
+                vec![Type::named("bigint"), Type::named("bigint")],
+                |_env, args| {
+                    let a = args[0].downcast::<BigInt>()?;
+                    let b = args[1].downcast::<BigInt>()?;
+
+                    if b.0.is_zero() {
+                        return Err(ExceptionKind::DivideByZero.into());
+                    }
+
+                    Ok(Value::new(BigInt(&a.0 / &b.0)))
+                }
+            ).into()),
+            Op::Lt => Some(BuiltinFn::new(
+                "bigint_lt",
+                Type::named("bit"),
+                vec![Type::named("bigint"), Type::named("bigint")],
+                |_env, args| {
+                    let a = args[0].downcast::<BigInt>()?;
+                    let b = args[1].downcast::<BigInt>()?;
+
+                    Ok(Value::new(Bit::new(a.0 < b.0)))
+                }
+            ).into()),
+            Op::Gt => Some(BuiltinFn::new(
+                "bigint_gt",
+                Type::named("bit"),
+                vec![Type::named("bigint"), Type::named("bigint")],
+                |_env, args| {
+                    let a = args[0].downcast::<BigInt>()?;
+                    let b = args[1].downcast::<BigInt>()?;
+
+                    Ok(Value::new(Bit::new(a.0 > b.0)))
+                }
+            ).into()),
+            Op::LtEq => Some(BuiltinFn::new(
+                "bigint_lte",
+                Type::named("bit"),
+                vec![Type::named("bigint"), Type::named("bigint")],
+                |_env, args| {
+                    let a = args[0].downcast::<BigInt>()?;
+                    let b = args[1].downcast::<BigInt>()?;
+
+                    Ok(Value::new(Bit::new(a.0 <= b.0)))
+                }
+            ).into()),
+            Op::GtEq => Some(BuiltinFn::new(
+                "bigint_gte",
+                Type::named("bit"),
+                vec![Type::named("bigint"), Type::named("bigint")],
+                |_env, args| {
+                    let a = args[0].downcast::<BigInt>()?;
+                    let b = args[1].downcast::<BigInt>()?;
+
+                    Ok(Value::new(Bit::new(a.0 >= b.0)))
+                }
+            ).into()),
+            Op::BitAnd => Some(BuiltinFn::new(
+                "bigint_and",
+                Type::named("bigint"),
+                vec![Type::named("bigint"), Type::named("bigint")],
+                |_env, args| {
+                    let a = args[0].downcast::<BigInt>()?;
+                    let b = args[1].downcast::<BigInt>()?;
+
+                    Ok(Value::new(BigInt(&a.0 & &b.0)))
+                }
+            ).into()),
+            Op::BitOr => Some(BuiltinFn::new(
+                "bigint_or",
+                Type::named("bigint"),
+                vec![Type::named("bigint"), Type::named("bigint")],
+                |_env, args| {
+                    let a = args[0].downcast::<BigInt>()?;
+                    let b = args[1].downcast::<BigInt>()?;
+
+                    Ok(Value::new(BigInt(&a.0 | &b.0)))
+                }
+            ).into()),
+            Op::BitXor => Some(BuiltinFn::new(
+                "bigint_xor",
+                Type::named("bigint"),
+                vec![Type::named("bigint"), Type::named("bigint")],
+                |_env, args| {
+                    let a = args[0].downcast::<BigInt>()?;
+                    let b = args[1].downcast::<BigInt>()?;
+
+                    Ok(Value::new(BigInt(&a.0 ^ &b.0)))
+                }
+            ).into()),
+            _ => None,
+        }
+    }
+}
+
+impl Deref for BigInt {
+    type Target = num_bigint::BigInt;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
@@ -1,5 +1,5 @@
-use crate::interp::Env;
-use super::{Value, ValItem, Fn, Result, Op, BuiltinFn, Exception, Type};
+use std::io::{self, Write};
+use super::{Value, ValItem, Fn, Result, Op, BuiltinFn, ExceptionKind, Type};
 
 pub struct Bit(bool);
 
@@ -18,7 +18,7 @@ unsafe impl<'ip> ValItem<'ip> for Bit {
         if ty == Type::named("bit") {
             Ok(())
         } else {
-            Err(Exception::InvalidType(Type::named("bit"), ty))
+            Err(ExceptionKind::InvalidType(Type::named("bit"), ty).into())
         }
     }
 
@@ -26,10 +26,14 @@ unsafe impl<'ip> ValItem<'ip> for Bit {
         Box::new(Bit(self.0))
     }
 
-    fn ty(&self, env: &mut Env<'_>) -> Type {
+    fn ty(&self) -> Type {
         Type::named("bit")
     }
 
+    fn write(&self, w: &mut dyn Write) -> io::Result<()> {
+        write!(w, "{}", if self.0 { "1b" } else { "0b" })
+    }
+
     fn get_field(&self, _name: &str) -> Option<Value<'ip>> {
         None
     }
@@ -41,15 +45,88 @@ unsafe impl<'ip> ValItem<'ip> for Bit {
                 Type::named("bit"),
                 vec![Type::named("bit"), Type::named("bit")],
                 |_env, args| {
-                    if args.len() != 2 {
-                        panic!("{:?}", args);
-                    }
                     let a = args[0].downcast::<Bit>()?;
-                    let b = args[0].downcast::<Bit>()?;
+                    let b = args[1].downcast::<Bit>()?;
 
                     Ok(Value::new(Bit(a.0 == b.0)))
                 }
             ).into()),
+            Op::Neq => Some(BuiltinFn::new(
+                "bit_neq",
+                Type::named("bit"),
+                vec![Type::named("bit"), Type::named("bit")],
+                |_env, args| {
+                    let a = args[0].downcast::<Bit>()?;
+                    let b = args[1].downcast::<Bit>()?;
+
+                    Ok(Value::new(Bit(a.0 != b.0)))
+                }
+            ).into()),
+            Op::Inv => Some(BuiltinFn::new(
+                "bit_inv",
+                Type::named("bit"),
+                vec![Type::named("bit")],
+                |_env, args| {
+                    let a = args[0].downcast::<Bit>()?;
+
+                    Ok(Value::new(Bit(!a.0)))
+                }
+            ).into()),
+            Op::BitAnd => Some(BuiltinFn::new(
+                "bit_and",
+                Type::named("bit"),
+                vec![Type::named("bit"), Type::named("bit")],
+                |_env, args| {
+                    let a = args[0].downcast::<Bit>()?;
+                    let b = args[1].downcast::<Bit>()?;
+
+                    Ok(Value::new(Bit(a.0 & b.0)))
+                }
+            ).into()),
+            Op::BitOr => Some(BuiltinFn::new(
+                "bit_or",
+                Type::named("bit"),
+                vec![Type::named("bit"), Type::named("bit")],
+                |_env, args| {
+                    let a = args[0].downcast::<Bit>()?;
+                    let b = args[1].downcast::<Bit>()?;
+
+                    Ok(Value::new(Bit(a.0 | b.0)))
+                }
+            ).into()),
+            Op::BitXor => Some(BuiltinFn::new(
+                "bit_xor",
+                Type::named("bit"),
+                vec![Type::named("bit"), Type::named("bit")],
+                |_env, args| {
+                    let a = args[0].downcast::<Bit>()?;
+                    let b = args[1].downcast::<Bit>()?;
+
+                    Ok(Value::new(Bit(a.0 ^ b.0)))
+                }
+            ).into()),
+            Op::And => Some(BuiltinFn::new(
+                "bit_logical_and",
+                Type::named("bit"),
+                vec![Type::named("bit"), Type::named("bit")],
+                |_env, args| {
+                    let a = args[0].downcast::<Bit>()?;
+                    let b = args[1].downcast::<Bit>()?;
+
+                    Ok(Value::new(Bit(a.0 && b.0)))
+                }
+            ).into()),
+            Op::Or => Some(BuiltinFn::new(
+                "bit_logical_or",
+                Type::named("bit"),
+                vec![Type::named("bit"), Type::named("bit")],
+                |_env, args| {
+                    let a = args[0].downcast::<Bit>()?;
+                    let b = args[1].downcast::<Bit>()?;
+
+                    Ok(Value::new(Bit(a.0 || b.0)))
+                }
+            ).into()),
             _ => None,
         }
     }
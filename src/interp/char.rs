@@ -1,5 +1,5 @@
-use crate::interp::Env;
-use super::{ValItem, Value, Fn, Result, Op, Exception, Type};
+use std::io::{self, Write};
+use super::{ValItem, Value, Bit, Fn, Result, Op, BuiltinFn, ExceptionKind, Type};
 
 pub struct Char(char);
 
@@ -7,6 +7,10 @@ impl Char {
     pub fn new(c: char) -> Char {
         Char(c)
     }
+
+    pub fn val(&self) -> char {
+        self.0
+    }
 }
 
 unsafe impl<'ip> ValItem<'ip> for Char {
@@ -14,7 +18,7 @@ unsafe impl<'ip> ValItem<'ip> for Char {
         if ty == Type::named("char") {
             Ok(())
         } else {
-            Err(Exception::InvalidType(Type::named("char"), ty))
+            Err(ExceptionKind::InvalidType(Type::named("char"), ty).into())
         }
     }
 
@@ -22,15 +26,87 @@ unsafe impl<'ip> ValItem<'ip> for Char {
         Box::new(Char(self.0))
     }
 
-    fn ty(&self, env: &mut Env<'_>) -> Type {
+    fn ty(&self) -> Type {
         Type::named("char")
     }
 
+    fn write(&self, w: &mut dyn Write) -> io::Result<()> {
+        write!(w, "{}", self.0)
+    }
+
     fn get_field(&self, _name: &str) -> Option<Value<'ip>> {
         None
     }
 
     fn get_op(&self, op: Op) -> Option<Fn<'ip>> {
-        todo!()
+        match op {
+            Op::Eq => Some(BuiltinFn::new(
+                "char_eq",
+                Type::named("bit"),
+                vec![Type::named("char"), Type::named("char")],
+                |_env, args| {
+                    let a = args[0].downcast::<Char>()?;
+                    let b = args[1].downcast::<Char>()?;
+
+                    Ok(Value::new(Bit::new(a.0 == b.0)))
+                }
+            ).into()),
+            Op::Neq => Some(BuiltinFn::new(
+                "char_neq",
+                Type::named("bit"),
+                vec![Type::named("char"), Type::named("char")],
+                |_env, args| {
+                    let a = args[0].downcast::<Char>()?;
+                    let b = args[1].downcast::<Char>()?;
+
+                    Ok(Value::new(Bit::new(a.0 != b.0)))
+                }
+            ).into()),
+            Op::Lt => Some(BuiltinFn::new(
+                "char_lt",
+                Type::named("bit"),
+                vec![Type::named("char"), Type::named("char")],
+                |_env, args| {
+                    let a = args[0].downcast::<Char>()?;
+                    let b = args[1].downcast::<Char>()?;
+
+                    Ok(Value::new(Bit::new(a.0 < b.0)))
+                }
+            ).into()),
+            Op::Gt => Some(BuiltinFn::new(
+                "char_gt",
+                Type::named("bit"),
+                vec![Type::named("char"), Type::named("char")],
+                |_env, args| {
+                    let a = args[0].downcast::<Char>()?;
+                    let b = args[1].downcast::<Char>()?;
+
+                    Ok(Value::new(Bit::new(a.0 > b.0)))
+                }
+            ).into()),
+            Op::LtEq => Some(BuiltinFn::new(
+                "char_lte",
+                Type::named("bit"),
+                vec![Type::named("char"), Type::named("char")],
+                |_env, args| {
+                    let a = args[0].downcast::<Char>()?;
+                    let b = args[1].downcast::<Char>()?;
+
+                    Ok(Value::new(Bit::new(a.0 <= b.0)))
+                }
+            ).into()),
+            Op::GtEq => Some(BuiltinFn::new(
+                "char_gte",
+                Type::named("bit"),
+                vec![Type::named("char"), Type::named("char")],
+                |_env, args| {
+                    let a = args[0].downcast::<Char>()?;
+                    let b = args[1].downcast::<Char>()?;
+
+                    Ok(Value::new(Bit::new(a.0 >= b.0)))
+                }
+            ).into()),
+            _ => None,
+        }
     }
 }
@@ -1,6 +1,6 @@
+use std::io::{self, Write};
 use std::ops::Deref;
-use crate::interp::Env;
-use super::{ValItem, Op, Exception, Value, Fn, Result, Type};
+use super::{ValItem, Op, ExceptionKind, Value, Bit, Char, Fn, BuiltinFn, Int, Result, Type};
 
 pub struct CharArray(String);
 
@@ -15,7 +15,7 @@ unsafe impl<'ip> ValItem<'ip> for CharArray {
         if ty == Type::named("chararray") {
             Ok(())
         } else {
-            Err(Exception::InvalidType(Type::named("chararray"), ty))
+            Err(ExceptionKind::InvalidType(Type::named("chararray"), ty).into())
         }
     }
 
@@ -23,16 +23,110 @@ unsafe impl<'ip> ValItem<'ip> for CharArray {
         Box::new(CharArray(self.0.clone()))
     }
 
-    fn ty(&self, env: &mut Env<'_>) -> Type {
+    fn ty(&self) -> Type {
         Type::named("chararray")
     }
 
-    fn get_field(&self, _name: &str) -> Option<Value<'ip>> {
-        None
+    fn write(&self, w: &mut dyn Write) -> io::Result<()> {
+        write!(w, "{}", self.0)
+    }
+
+    fn get_field(&self, name: &str) -> Option<Value<'ip>> {
+        match name {
+            "len" => Some(Value::new(Int::new(self.0.chars().count() as i128))),
+            _ => None,
+        }
+    }
+
+    fn get_index(&self, idx: i128) -> Result<Value<'ip>> {
+        usize::try_from(idx)
+            .ok()
+            .and_then(|i| self.0.chars().nth(i))
+            .map(|c| Value::new(Char::new(c)))
+            .ok_or_else(|| ExceptionKind::IndexOutOfRange(idx, self.0.chars().count()).into())
     }
 
     fn get_op(&self, op: Op) -> Option<Fn<'ip>> {
-        todo!()
+        match op {
+            Op::Eq => Some(BuiltinFn::new(
+                "chararray_eq",
+                Type::named("bit"),
+                vec![Type::named("chararray"), Type::named("chararray")],
+                |_env, args| {
+                    let a = args[0].downcast::<CharArray>()?;
+                    let b = args[1].downcast::<CharArray>()?;
+
+                    Ok(Value::new(Bit::new(a.0 == b.0)))
+                }
+            ).into()),
+            Op::Neq => Some(BuiltinFn::new(
+                "chararray_neq",
+                Type::named("bit"),
+                vec![Type::named("chararray"), Type::named("chararray")],
+                |_env, args| {
+                    let a = args[0].downcast::<CharArray>()?;
+                    let b = args[1].downcast::<CharArray>()?;
+
+                    Ok(Value::new(Bit::new(a.0 != b.0)))
+                }
+            ).into()),
+            Op::Add => Some(BuiltinFn::new(
+                "chararray_concat",
+                Type::named("chararray"),
+                vec![Type::named("chararray"), Type::named("chararray")],
+                |_env, args| {
+                    let a = args[0].downcast::<CharArray>()?;
+                    let b = args[1].downcast::<CharArray>()?;
+
+                    Ok(Value::new(CharArray(format!("{}{}", a.0, b.0))))
+                }
+            ).into()),
+            Op::Lt => Some(BuiltinFn::new(
+                "chararray_lt",
+                Type::named("bit"),
+                vec![Type::named("chararray"), Type::named("chararray")],
+                |_env, args| {
+                    let a = args[0].downcast::<CharArray>()?;
+                    let b = args[1].downcast::<CharArray>()?;
+
+                    Ok(Value::new(Bit::new(a.0 < b.0)))
+                }
+            ).into()),
+            Op::Gt => Some(BuiltinFn::new(
+                "chararray_gt",
+                Type::named("bit"),
+                vec![Type::named("chararray"), Type::named("chararray")],
+                |_env, args| {
+                    let a = args[0].downcast::<CharArray>()?;
+                    let b = args[1].downcast::<CharArray>()?;
+
+                    Ok(Value::new(Bit::new(a.0 > b.0)))
+                }
+            ).into()),
+            Op::LtEq => Some(BuiltinFn::new(
+                "chararray_lte",
+                Type::named("bit"),
+                vec![Type::named("chararray"), Type::named("chararray")],
+                |_env, args| {
+                    let a = args[0].downcast::<CharArray>()?;
+                    let b = args[1].downcast::<CharArray>()?;
+
+                    Ok(Value::new(Bit::new(a.0 <= b.0)))
+                }
+            ).into()),
+            Op::GtEq => Some(BuiltinFn::new(
+                "chararray_gte",
+                Type::named("bit"),
+                vec![Type::named("chararray"), Type::named("chararray")],
+                |_env, args| {
+                    let a = args[0].downcast::<CharArray>()?;
+                    let b = args[1].downcast::<CharArray>()?;
+
+                    Ok(Value::new(Bit::new(a.0 >= b.0)))
+                }
+            ).into()),
+            _ => None,
+        }
     }
 }
 
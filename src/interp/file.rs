@@ -0,0 +1,122 @@
+use std::cell::RefCell;
+use std::fs;
+use std::io::{self, Write};
+use std::os::unix::io::{FromRawFd, RawFd};
+use std::rc::Rc;
+use super::{ValItem, Value, Bit, Fn, Result, BuiltinFn, ExceptionKind, Type, Op};
+
+/// An open file. Copies of the value share the same file, so they all see the same position, and
+/// closing one closes them all.
+pub struct File {
+    name: Rc<str>,
+    file: Rc<RefCell<Option<fs::File>>>,
+}
+
+impl File {
+    /// Open the file at `path`. `mode` is one of `read`, `write`, `append` or `truncate`, and
+    /// every mode but `read` creates the file if it doesn't exist.
+    pub fn open(path: &str, mode: &str) -> Result<File> {
+        let mut options = fs::File::options();
+        match mode {
+            "read" => options.read(true),
+            "write" => options.read(true).write(true).create(true),
+            "append" => options.read(true).append(true).create(true),
+            "truncate" => options.read(true).write(true).create(true).truncate(true),
+            _ => return Err(ExceptionKind::InvalidOpenMode(mode.to_string()).into()),
+        };
+        Ok(File::new(path, options.open(path)?))
+    }
+
+    /// Wrap a copy of the file descriptor `fd`, which raises if `fd` isn't open
+    pub fn from_fd(fd: i128) -> Result<File> {
+        let raw = RawFd::try_from(fd).map_err(|_| ExceptionKind::InvalidFd(fd))?;
+        // Duplicating the fd checks it's open, and leaves the original to whoever owns it
+        let owned = unsafe { libc::dup(raw) };
+        if owned < 0 {
+            return Err(ExceptionKind::InvalidFd(fd).into());
+        }
+        Ok(File::new(&format!("fd {}", fd), unsafe { fs::File::from_raw_fd(owned) }))
+    }
+
+    fn new(name: &str, file: fs::File) -> File {
+        File {
+            name: name.into(),
+            file: Rc::new(RefCell::new(Some(file))),
+        }
+    }
+
+    /// Run `f` on the open file, raising if it's been closed
+    pub fn with<R>(&self, f: impl FnOnce(&mut fs::File) -> Result<R>) -> Result<R> {
+        match &mut *self.file.borrow_mut() {
+            Some(file) => f(file),
+            None => Err(ExceptionKind::FileClosed(self.name.to_string()).into()),
+        }
+    }
+
+    pub fn close(&self) -> Result<()> {
+        match self.file.borrow_mut().take() {
+            Some(_) => Ok(()),
+            None => Err(ExceptionKind::FileClosed(self.name.to_string()).into()),
+        }
+    }
+}
+
+unsafe impl<'ip> ValItem<'ip> for File {
+    fn allow_cast(ty: Type) -> Result<()> {
+        if ty == Type::named("file") {
+            Ok(())
+        } else {
+            Err(ExceptionKind::InvalidType(Type::named("file"), ty).into())
+        }
+    }
+
+    fn clone(&self) -> Box<dyn ValItem<'ip>> {
+        Box::new(File {
+            name: Rc::clone(&self.name),
+            file: Rc::clone(&self.file),
+        })
+    }
+
+    fn ty(&self) -> Type {
+        Type::named("file")
+    }
+
+    fn write(&self, w: &mut dyn Write) -> io::Result<()> {
+        write!(w, "<file {}>", self.name)
+    }
+
+    fn get_field(&self, name: &str) -> Option<Value<'ip>> {
+        match name {
+            "open" => Some(Value::new(Bit::new(self.file.borrow().is_some()))),
+            _ => None,
+        }
+    }
+
+    fn get_op(&self, op: Op) -> Option<Fn<'ip>> {
+        match op {
+            Op::Eq => Some(BuiltinFn::new(
+                "file_eq",
+                Type::named("bit"),
+                vec![Type::named("file"), Type::named("file")],
+                |_env, args| {
+                    let a = args[0].downcast::<File>()?;
+                    let b = args[1].downcast::<File>()?;
+
+                    Ok(Value::new(Bit::new(Rc::ptr_eq(&a.file, &b.file))))
+                }
+            ).into()),
+            Op::Neq => Some(BuiltinFn::new(
+                "file_neq",
+                Type::named("bit"),
+                vec![Type::named("file"), Type::named("file")],
+                |_env, args| {
+                    let a = args[0].downcast::<File>()?;
+                    let b = args[1].downcast::<File>()?;
+
+                    Ok(Value::new(Bit::new(!Rc::ptr_eq(&a.file, &b.file))))
+                }
+            ).into()),
+            _ => None,
+        }
+    }
+}
@@ -1,5 +1,5 @@
-use crate::interp::Env;
-use super::{ValItem, Value, Fn, Op, Exception, Result, Type};
+use std::io::{self, Write};
+use super::{ValItem, Value, Bit, Env, Fn, Op, BuiltinFn, ExceptionKind, Result, Type};
 
 pub struct Float(f64);
 
@@ -11,6 +11,24 @@ impl Float {
     pub fn val(&self) -> f64 {
         self.0
     }
+
+    /// Wrap a float, raising if it's NaN and the environment is strict about it. `source` describes
+    /// what produced the float, for the exception.
+    pub(crate) fn strict<'ip>(
+        env: &Env<'ip>,
+        val: f64,
+        source: impl FnOnce() -> String,
+    ) -> Result<Value<'ip>> {
+        if val.is_nan() && env.is_strict_nan() {
+            return Err(ExceptionKind::NotANumber(source()).into());
+        }
+        Ok(Value::new(Float(val)))
+    }
+
+    /// Wrap the result of an operator, raising if it's NaN and the environment is strict about it
+    fn result<'ip>(env: &Env<'ip>, op: Op, val: f64) -> Result<Value<'ip>> {
+        Float::strict(env, val, || format!("Operator {}", op.pretty()))
+    }
 }
 
 unsafe impl<'ip> ValItem<'ip> for Float {
@@ -18,7 +36,7 @@ unsafe impl<'ip> ValItem<'ip> for Float {
         if ty == Type::named("float") {
             Ok(())
         } else {
-            Err(Exception::InvalidType(Type::named("float"), ty))
+            Err(ExceptionKind::InvalidType(Type::named("float"), ty).into())
         }
     }
 
@@ -26,15 +44,141 @@ unsafe impl<'ip> ValItem<'ip> for Float {
         Box::new(Float(self.0))
     }
 
-    fn ty(&self, env: &mut Env<'_>) -> Type {
+    fn ty(&self) -> Type {
         Type::named("float")
     }
 
+    fn write(&self, w: &mut dyn Write) -> io::Result<()> {
+        write!(w, "{}", self.0)
+    }
+
     fn get_field(&self, _name: &str) -> Option<Value<'ip>> {
         None
     }
 
     fn get_op(&self, op: Op) -> Option<Fn<'ip>> {
-        todo!()
+        match op {
+            Op::Eq => Some(BuiltinFn::new(
+                "float_eq",
+                Type::named("bit"),
+                vec![Type::named("float"), Type::named("float")],
+                |_env, args| {
+                    let a = args[0].downcast::<Float>()?;
+                    let b = args[1].downcast::<Float>()?;
+
+                    Ok(Value::new(Bit::new(a.0 == b.0)))
+                }
+            ).into()),
+            Op::Neq => Some(BuiltinFn::new(
+                "float_neq",
+                Type::named("bit"),
+                vec![Type::named("float"), Type::named("float")],
+                |_env, args| {
+                    let a = args[0].downcast::<Float>()?;
+                    let b = args[1].downcast::<Float>()?;
+
+                    Ok(Value::new(Bit::new(a.0 != b.0)))
+                }
+            ).into()),
+            Op::Neg => Some(BuiltinFn::new(
+                "float_neg",
+                Type::named("float"),
+                vec![Type::named("float")],
+                |_env, args| {
+                    let a = args[0].downcast::<Float>()?;
+
+                    Ok(Value::new(Float(-a.0)))
+                }
+            ).into()),
+            Op::Add => Some(BuiltinFn::new(
+                "float_add",
+                Type::named("float"),
+                vec![Type::named("float"), Type::named("float")],
+                |env, args| {
+                    let a = args[0].downcast::<Float>()?;
+                    let b = args[1].downcast::<Float>()?;
+
+                    Float::result(env, Op::Add, a.0 + b.0)
+                }
+            ).into()),
+            Op::Sub => Some(BuiltinFn::new(
+                "float_sub",
+                Type::named("float"),
+                vec![Type::named("float"), Type::named("float")],
+                |env, args| {
+                    let a = args[0].downcast::<Float>()?;
+                    let b = args[1].downcast::<Float>()?;
+
+                    Float::result(env, Op::Sub, a.0 - b.0)
+                }
+            ).into()),
+            Op::Mul => Some(BuiltinFn::new(
+                "float_mul",
+                Type::named("float"),
+                vec![Type::named("float"), Type::named("float")],
+                |env, args| {
+                    let a = args[0].downcast::<Float>()?;
+                    let b = args[1].downcast::<Float>()?;
+
+                    Float::result(env, Op::Mul, a.0 * b.0)
+                }
+            ).into()),
+            Op::Div => Some(BuiltinFn::new(
+                "float_div",
+                Type::named("float"),
+                vec![Type::named("float"), Type::named("float")],
+                |env, args| {
+                    let a = args[0].downcast::<Float>()?;
+                    let b = args[1].downcast::<Float>()?;
+
+                    Float::result(env, Op::Div, a.0 / b.0)
+                }
+            ).into()),
+            Op::Lt => Some(BuiltinFn::new(
+                "float_lt",
+                Type::named("bit"),
+                vec![Type::named("float"), Type::named("float")],
+                |_env, args| {
+                    let a = args[0].downcast::<Float>()?;
+                    let b = args[1].downcast::<Float>()?;
+
+                    Ok(Value::new(Bit::new(a.0 < b.0)))
+                }
+            ).into()),
+            Op::Gt => Some(BuiltinFn::new(
+                "float_gt",
+                Type::named("bit"),
+                vec![Type::named("float"), Type::named("float")],
+                |_env, args| {
+                    let a = args[0].downcast::<Float>()?;
+                    let b = args[1].downcast::<Float>()?;
+
+                    Ok(Value::new(Bit::new(a.0 > b.0)))
+                }
+            ).into()),
+            Op::LtEq => Some(BuiltinFn::new(
+                "float_lte",
+                Type::named("bit"),
+                vec![Type::named("float"), Type::named("float")],
+                |_env, args| {
+                    let a = args[0].downcast::<Float>()?;
+                    let b = args[1].downcast::<Float>()?;
+
+                    Ok(Value::new(Bit::new(a.0 <= b.0)))
+                }
+            ).into()),
+            Op::GtEq => Some(BuiltinFn::new(
+                "float_gte",
+                Type::named("bit"),
+                vec![Type::named("float"), Type::named("float")],
+                |_env, args| {
+                    let a = args[0].downcast::<Float>()?;
+                    let b = args[1].downcast::<Float>()?;
+
+                    Ok(Value::new(Bit::new(a.0 >= b.0)))
+                }
+            ).into()),
+            _ => None,
+        }
     }
 }
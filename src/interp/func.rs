@@ -1,38 +1,77 @@
-use crate::ast::FnDef;
-use super::{Value, ValItem, Op, BuiltinFn, Env, Result, Exception, Type};
+use std::io::{self, Write};
+use std::rc::Rc;
+use crate::vm::{self, FnProto};
+use super::{Value, ValItem, Op, Bit, BuiltinFn, CharArray, Env, Int, Result, ExceptionKind, Scope, Type, Union};
 
 #[derive(Clone, Debug)]
 pub enum Fn<'ip> {
-    User(&'ip FnDef),
+    /// A function defined in goose, along with the scope it was defined in
+    User(Rc<FnProto>, Scope<'ip>),
     Builtin(BuiltinFn),
+    /// The constructor for the variant at an index of a union type
+    Variant(Type, usize),
 }
 
 impl<'ip> Fn<'ip> {
     pub fn name(&self) -> &str {
         match self {
-            Fn::User(fd) => fd.name(),
-            Fn::Builtin(b) => &*b.name,
+            Fn::User(proto, _) => proto.name(),
+            Fn::Builtin(b) => &b.name,
+            Fn::Variant(ty, idx) => Union::variant_name(ty, *idx),
         }
     }
 
     pub fn ret_ty(&self) -> &Type {
         match self {
-            Fn::User(fd) => fd.ret_ty(),
+            Fn::User(proto, _) => proto.ret_ty(),
             Fn::Builtin(b) => &b.ret,
+            Fn::Variant(ty, _) => ty,
         }
     }
 
     pub fn arg_tys(&self) -> Vec<Type> {
         match self {
-            Fn::User(fd) => fd.arg_tys(),
+            Fn::User(proto, _) => proto.arg_tys(),
             Fn::Builtin(b) => b.args.clone(),
+            Fn::Variant(ty, idx) => Union::payload_ty(ty, *idx).into_iter().cloned().collect(),
+        }
+    }
+
+    /// Whether two values refer to the same function. Functions have identity, so two
+    /// definitions with the same body are still different functions, as are two closures created
+    /// by the same literal in different scopes.
+    pub fn same(&self, other: &Fn<'ip>) -> bool {
+        match (self, other) {
+            (Fn::User(a, a_scope), Fn::User(b, b_scope)) => {
+                Rc::ptr_eq(a, b) && a_scope.ptr_eq(b_scope)
+            }
+            (Fn::Builtin(a), Fn::Builtin(b)) => a.name() == b.name(),
+            (Fn::Variant(a, a_idx), Fn::Variant(b, b_idx)) => a == b && a_idx == b_idx,
+            _ => false,
+        }
+    }
+
+    /// Whether this function accepts any number of arguments, so has no meaningful argument types
+    pub fn is_variadic(&self) -> bool {
+        match self {
+            Fn::User(_, _) | Fn::Variant(_, _) => false,
+            Fn::Builtin(b) => b.is_variadic(),
+        }
+    }
+
+    /// The union type and variant index this function constructs, if it's a variant constructor
+    pub fn variant(&self) -> Option<(&Type, usize)> {
+        match self {
+            Fn::Variant(ty, idx) => Some((ty, *idx)),
+            _ => None,
         }
     }
 
     pub fn invoke(&self, env: &mut Env<'ip>, args: Vec<Value<'ip>>) -> Result<Value<'ip>> {
         match self {
-            Fn::User(fd) => fd.invoke(env, args),
+            Fn::User(proto, scope) => vm::call(env, proto, scope, args),
             Fn::Builtin(b) => b.invoke(env, &args),
+            Fn::Variant(ty, idx) => Ok(Value::new(Union::new(env, Clone::clone(ty), *idx, args)?)),
         }
     }
 }
@@ -42,7 +81,7 @@ unsafe impl<'ip> ValItem<'ip> for Fn<'ip> {
         if let Type::Fn(_, _) = ty {
             Ok(())
         } else {
-            Err(Exception::InvalidType(Type::Fn(Box::new(Type::named("any")), vec![]), ty))
+            Err(ExceptionKind::InvalidType(Type::Fn(Box::new(Type::named("any")), vec![]), ty).into())
         }
     }
 
@@ -50,23 +89,64 @@ unsafe impl<'ip> ValItem<'ip> for Fn<'ip> {
         Box::new(Clone::clone(self))
     }
 
-    fn ty(&self, env: &mut Env<'_>) -> Type {
-        Type::Fn(Box::new(Clone::clone(self.ret_ty())), self.arg_tys())
+    fn ty(&self) -> Type {
+        let ty = Type::Fn(Box::new(Clone::clone(self.ret_ty())), self.arg_tys());
+        // Type parameters are only bound for each call, so from outside they accept anything
+        match self {
+            Fn::User(proto, _) => {
+                let any = proto.generics.iter().map(|param| (param.clone(), Some(Type::named("any"))));
+                ty.substitute(&any.collect::<Vec<_>>())
+            }
+            _ => ty,
+        }
+    }
+
+    fn write(&self, w: &mut dyn Write) -> io::Result<()> {
+        write!(w, "<fn {}>", self.name())
     }
 
-    fn get_field(&self, _name: &str) -> Option<Value<'ip>> {
-        // TODO: Maybe functions have name/type fields?
-        None
+    fn get_field(&self, name: &str) -> Option<Value<'ip>> {
+        match name {
+            "name" => Some(Value::new(CharArray::new(self.name().to_string()))),
+            "arity" => Some(Value::new(Int::new(self.arg_tys().len() as i128))),
+            _ => None,
+        }
     }
 
     fn get_op(&self, op: Op) -> Option<Fn<'ip>> {
-        todo!()
+        let ty = ValItem::ty(self);
+        match op {
+            Op::Eq => Some(BuiltinFn::new(
+                "fn_eq",
+                Type::named("bit"),
+                vec![Clone::clone(&ty), ty],
+                |_env, args| {
+                    let a = args[0].downcast::<Fn<'_>>()?;
+                    let b = args[1].downcast::<Fn<'_>>()?;
+
+                    Ok(Value::new(Bit::new(a.same(b))))
+                }
+            ).into()),
+            Op::Neq => Some(BuiltinFn::new(
+                "fn_neq",
+                Type::named("bit"),
+                vec![Clone::clone(&ty), ty],
+                |_env, args| {
+                    let a = args[0].downcast::<Fn<'_>>()?;
+                    let b = args[1].downcast::<Fn<'_>>()?;
+
+                    Ok(Value::new(Bit::new(!a.same(b))))
+                }
+            ).into()),
+            _ => None,
+        }
     }
 }
 
-impl<'ip> From<&'ip FnDef> for Fn<'ip> {
-    fn from(fd: &'ip FnDef) -> Self {
-        Fn::User(fd)
+impl<'ip> Fn<'ip> {
+    /// Create a function from a compiled prototype, capturing the scope it's defined in
+    pub fn closure(proto: Rc<FnProto>, scope: Scope<'ip>) -> Fn<'ip> {
+        Fn::User(proto, scope)
     }
 }
 
@@ -0,0 +1,94 @@
+use std::fmt;
+use std::fs;
+use std::io::{self, BufWriter, Write};
+use std::path::PathBuf;
+
+enum Sink {
+    /// A file that hasn't been opened yet, because nothing has been written to it
+    Unopened(PathBuf),
+    File(PathBuf, BufWriter<fs::File>),
+    Memory(Vec<u8>),
+}
+
+/// Where `write(honk, ...)` sends its output. A file sink is truncated once, when it's opened,
+/// and appended to for the rest of the run. Output is buffered until [`Honk::flush`] or the sink
+/// is dropped.
+pub struct Honk {
+    sink: Sink,
+}
+
+impl Default for Honk {
+    fn default() -> Self {
+        Honk {
+            sink: Sink::Unopened(PathBuf::from(Honk::DEFAULT_PATH)),
+        }
+    }
+}
+
+impl Honk {
+    /// The file output goes to when no other sink is given. Unless it's opened up front, as goose's
+    /// command line does, it isn't created until something is written to it.
+    pub const DEFAULT_PATH: &'static str = "honk";
+
+    /// Send output to the file at `path`, truncating it now. Anything written to the previous
+    /// sink is flushed first.
+    pub fn open(&mut self, path: impl Into<PathBuf>) -> io::Result<()> {
+        self.flush()?;
+        let path = path.into();
+        let file = fs::File::create(&path)?;
+        self.sink = Sink::File(path, BufWriter::new(file));
+        Ok(())
+    }
+
+    /// Send output to an in-memory buffer instead of a file, which [`Honk::memory`] reads back
+    pub fn to_memory(&mut self) -> io::Result<()> {
+        self.flush()?;
+        self.sink = Sink::Memory(Vec::new());
+        Ok(())
+    }
+
+    /// Everything written so far, if output is going to memory
+    pub fn memory(&self) -> Option<&[u8]> {
+        match &self.sink {
+            Sink::Memory(buf) => Some(buf),
+            _ => None,
+        }
+    }
+
+    /// The file output goes to, if it isn't going to memory
+    pub fn path(&self) -> Option<&PathBuf> {
+        match &self.sink {
+            Sink::Unopened(path) | Sink::File(path, _) => Some(path),
+            Sink::Memory(_) => None,
+        }
+    }
+
+    /// The writer for the sink, opening its file on first use
+    pub fn writer(&mut self) -> io::Result<&mut dyn Write> {
+        if let Sink::Unopened(path) = &self.sink {
+            let path = path.clone();
+            self.open(path)?;
+        }
+        match &mut self.sink {
+            Sink::File(_, w) => Ok(w),
+            Sink::Memory(buf) => Ok(buf),
+            Sink::Unopened(_) => unreachable!("honk file wasn't opened"),
+        }
+    }
+
+    pub fn flush(&mut self) -> io::Result<()> {
+        match &mut self.sink {
+            Sink::File(_, w) => w.flush(),
+            Sink::Unopened(_) | Sink::Memory(_) => Ok(()),
+        }
+    }
+}
+
+impl fmt::Debug for Honk {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.sink {
+            Sink::Memory(buf) => write!(f, "Honk(<{} bytes in memory>)", buf.len()),
+            Sink::Unopened(path) | Sink::File(path, _) => write!(f, "Honk({:?})", path),
+        }
+    }
+}
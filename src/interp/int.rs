@@ -1,8 +1,7 @@
 use std::io;
 use std::io::Write;
 use std::ops::Deref;
-use crate::interp::Env;
-use super::{ValItem, Value, Op, Bit, Fn, Result, BuiltinFn, Exception, Type};
+use super::{ValItem, Value, Op, Bit, Fn, Result, BuiltinFn, ExceptionKind, Type};
 
 #[derive(Clone)]
 pub struct Int(i128);
@@ -22,7 +21,7 @@ unsafe impl<'ip> ValItem<'ip> for Int {
         if ty == Type::named("int") {
             Ok(())
         } else {
-            Err(Exception::InvalidType(Type::named("int"), ty))
+            Err(ExceptionKind::InvalidType(Type::named("int"), ty).into())
         }
     }
 
@@ -30,8 +29,7 @@ unsafe impl<'ip> ValItem<'ip> for Int {
         Box::new(Int(self.0))
     }
 
-    fn ty(&self, env: &mut Env<'_>) -> Type {
-
+    fn ty(&self) -> Type {
         Type::named("int")
     }
 
@@ -61,7 +59,7 @@ unsafe impl<'ip> ValItem<'ip> for Int {
                 }
             ).into()),
             Op::Neq => Some(BuiltinFn::new(
-                "int_eq",
+                "int_neq",
                 Type::named("bit"),
                 vec![Type::named("int"), Type::named("int")],
                 |_env, args| {
@@ -75,19 +73,159 @@ unsafe impl<'ip> ValItem<'ip> for Int {
                     Ok(Value::new(Bit::new(a.0 != b.0)))
                 }
             ).into()),
+            Op::Inv => Some(BuiltinFn::new(
+                "int_inv",
+                Type::named("int"),
+                vec![Type::named("int")],
+                |_env, args| {
+                    let a = args[0].downcast::<Int>()?;
+
+                    Ok(Value::new(Int(!a.0)))
+                }
+            ).into()),
+            Op::Neg => Some(BuiltinFn::new(
+                "int_neg",
+                Type::named("int"),
+                vec![Type::named("int")],
+                |_env, args| {
+                    let a = args[0].downcast::<Int>()?;
+
+                    a.0.checked_neg()
+                        .map(|i| Value::new(Int(i)))
+                        .ok_or_else(|| ExceptionKind::Overflow(Op::Neg).into())
+                }
+            ).into()),
             Op::Add => Some(BuiltinFn::new(
                 "int_add",
                 Type::named("int"),
                 vec![Type::named("int"), Type::named("int")],
                 |_env, args| {
-                    if args.len() != 2 {
-                        panic!("{:?}", args);
+                    let a = args[0].downcast::<Int>()?;
+                    let b = args[1].downcast::<Int>()?;
+
+                    a.0.checked_add(b.0)
+                        .map(|i| Value::new(Int(i)))
+                        .ok_or_else(|| ExceptionKind::Overflow(Op::Add).into())
+                }
+            ).into()),
+            Op::Sub => Some(BuiltinFn::new(
+                "int_sub",
+                Type::named("int"),
+                vec![Type::named("int"), Type::named("int")],
+                |_env, args| {
+                    let a = args[0].downcast::<Int>()?;
+                    let b = args[1].downcast::<Int>()?;
+
+                    a.0.checked_sub(b.0)
+                        .map(|i| Value::new(Int(i)))
+                        .ok_or_else(|| ExceptionKind::Overflow(Op::Sub).into())
+                }
+            ).into()),
+            Op::Mul => Some(BuiltinFn::new(
+                "int_mul",
+                Type::named("int"),
+                vec![Type::named("int"), Type::named("int")],
+                |_env, args| {
+                    let a = args[0].downcast::<Int>()?;
+                    let b = args[1].downcast::<Int>()?;
+
+                    a.0.checked_mul(b.0)
+                        .map(|i| Value::new(Int(i)))
+                        .ok_or_else(|| ExceptionKind::Overflow(Op::Mul).into())
+                }
+            ).into()),
+            Op::Div => Some(BuiltinFn::new(
+                "int_div",
+                Type::named("int"),
+                vec![Type::named("int"), Type::named("int")],
+                |_env, args| {
+                    let a = args[0].downcast::<Int>()?;
+                    let b = args[1].downcast::<Int>()?;
+
+                    if b.0 == 0 {
+                        return Err(ExceptionKind::DivideByZero.into());
                     }
 
+                    a.0.checked_div(b.0)
+                        .map(|i| Value::new(Int(i)))
+                        .ok_or_else(|| ExceptionKind::Overflow(Op::Div).into())
+                }
+            ).into()),
+            Op::Lt => Some(BuiltinFn::new(
+                "int_lt",
+                Type::named("bit"),
+                vec![Type::named("int"), Type::named("int")],
+                |_env, args| {
+                    let a = args[0].downcast::<Int>()?;
+                    let b = args[1].downcast::<Int>()?;
+
+                    Ok(Value::new(Bit::new(a.0 < b.0)))
+                }
+            ).into()),
+            Op::Gt => Some(BuiltinFn::new(
+                "int_gt",
+                Type::named("bit"),
+                vec![Type::named("int"), Type::named("int")],
+                |_env, args| {
+                    let a = args[0].downcast::<Int>()?;
+                    let b = args[1].downcast::<Int>()?;
+
+                    Ok(Value::new(Bit::new(a.0 > b.0)))
+                }
+            ).into()),
+            Op::LtEq => Some(BuiltinFn::new(
+                "int_lte",
+                Type::named("bit"),
+                vec![Type::named("int"), Type::named("int")],
+                |_env, args| {
+                    let a = args[0].downcast::<Int>()?;
+                    let b = args[1].downcast::<Int>()?;
+
+                    Ok(Value::new(Bit::new(a.0 <= b.0)))
+                }
+            ).into()),
+            Op::GtEq => Some(BuiltinFn::new(
+                "int_gte",
+                Type::named("bit"),
+                vec![Type::named("int"), Type::named("int")],
+                |_env, args| {
+                    let a = args[0].downcast::<Int>()?;
+                    let b = args[1].downcast::<Int>()?;
+
+                    Ok(Value::new(Bit::new(a.0 >= b.0)))
+                }
+            ).into()),
+            Op::BitAnd => Some(BuiltinFn::new(
+                "int_and",
+                Type::named("int"),
+                vec![Type::named("int"), Type::named("int")],
+                |_env, args| {
+                    let a = args[0].downcast::<Int>()?;
+                    let b = args[1].downcast::<Int>()?;
+
+                    Ok(Value::new(Int(a.0 & b.0)))
+                }
+            ).into()),
+            Op::BitOr => Some(BuiltinFn::new(
+                "int_or",
+                Type::named("int"),
+                vec![Type::named("int"), Type::named("int")],
+                |_env, args| {
+                    let a = args[0].downcast::<Int>()?;
+                    let b = args[1].downcast::<Int>()?;
+
+                    Ok(Value::new(Int(a.0 | b.0)))
+                }
+            ).into()),
+            Op::BitXor => Some(BuiltinFn::new(
+                "int_xor",
+                Type::named("int"),
+                vec![Type::named("int"), Type::named("int")],
+                |_env, args| {
                     let a = args[0].downcast::<Int>()?;
                     let b = args[1].downcast::<Int>()?;
 
-                    Ok(Value::new(Int(a.0 + b.0)))
+                    Ok(Value::new(Int(a.0 ^ b.0)))
                 }
             ).into()),
             _ => None,
@@ -0,0 +1,86 @@
+use std::io::{self, Write};
+use std::rc::Rc;
+use super::{ValItem, Value, Bit, Fn, Result, BuiltinFn, ExceptionKind, Type, Op};
+
+/// The definitions an imported file made at its top level, reached through its namespace with
+/// `module.name`
+pub struct Module<'ip> {
+    path: Rc<str>,
+    defs: Rc<Vec<(String, Value<'ip>)>>,
+}
+
+impl<'ip> Module<'ip> {
+    pub fn new(path: &str, mut defs: Vec<(String, Value<'ip>)>) -> Module<'ip> {
+        defs.sort_by(|(a, _), (b, _)| a.cmp(b));
+        Module {
+            path: path.into(),
+            defs: Rc::new(defs),
+        }
+    }
+
+    /// The path the module was loaded from
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Value<'ip>> {
+        self.defs.iter().find(|(def, _)| def == name).map(|(_, val)| val)
+    }
+}
+
+unsafe impl<'ip> ValItem<'ip> for Module<'ip> {
+    fn allow_cast(ty: Type) -> Result<()> {
+        if ty == Type::named("module") {
+            Ok(())
+        } else {
+            Err(ExceptionKind::InvalidType(Type::named("module"), ty).into())
+        }
+    }
+
+    fn clone(&self) -> Box<dyn ValItem<'ip> + 'ip> {
+        Box::new(Module {
+            path: Rc::clone(&self.path),
+            defs: Rc::clone(&self.defs),
+        })
+    }
+
+    fn ty(&self) -> Type {
+        Type::named("module")
+    }
+
+    fn write(&self, w: &mut dyn Write) -> io::Result<()> {
+        write!(w, "<module {}>", self.path)
+    }
+
+    fn get_field(&self, name: &str) -> Option<Value<'ip>> {
+        self.get(name).cloned()
+    }
+
+    fn get_op(&self, op: Op) -> Option<Fn<'ip>> {
+        match op {
+            Op::Eq => Some(BuiltinFn::new(
+                "module_eq",
+                Type::named("bit"),
+                vec![Type::named("module"), Type::named("module")],
+                |_env, args| {
+                    let a = args[0].downcast::<Module<'_>>()?;
+                    let b = args[1].downcast::<Module<'_>>()?;
+
+                    Ok(Value::new(Bit::new(Rc::ptr_eq(&a.defs, &b.defs))))
+                }
+            ).into()),
+            Op::Neq => Some(BuiltinFn::new(
+                "module_neq",
+                Type::named("bit"),
+                vec![Type::named("module"), Type::named("module")],
+                |_env, args| {
+                    let a = args[0].downcast::<Module<'_>>()?;
+                    let b = args[1].downcast::<Module<'_>>()?;
+
+                    Ok(Value::new(Bit::new(!Rc::ptr_eq(&a.defs, &b.defs))))
+                }
+            ).into()),
+            _ => None,
+        }
+    }
+}
@@ -1,5 +1,5 @@
-use crate::interp::Env;
-use super::{ValItem, Value, Fn, Result, Exception, Op, Type};
+use std::io::{self, Write};
+use super::{ValItem, Value, Bit, Fn, Result, BuiltinFn, ExceptionKind, Op, Type};
 
 pub struct Null;
 
@@ -8,7 +8,7 @@ unsafe impl<'ip> ValItem<'ip> for Null {
         if ty == Type::named("null") {
             Ok(())
         } else {
-            Err(Exception::InvalidType(Type::named("null"), ty))
+            Err(ExceptionKind::InvalidType(Type::named("null"), ty).into())
         }
     }
 
@@ -16,15 +16,37 @@ unsafe impl<'ip> ValItem<'ip> for Null {
         Box::new(Null)
     }
 
-    fn ty(&self, env: &mut Env<'_>) -> Type {
+    fn ty(&self) -> Type {
         Type::named("null")
     }
 
+    fn write(&self, w: &mut dyn Write) -> io::Result<()> {
+        write!(w, "null")
+    }
+
     fn get_field(&self, _name: &str) -> Option<Value<'ip>> {
         None
     }
 
     fn get_op(&self, op: Op) -> Option<Fn<'ip>> {
-        todo!()
+        match op {
+            Op::Eq => Some(BuiltinFn::new(
+                "null_eq",
+                Type::named("bit"),
+                vec![Type::named("null"), Type::named("null")],
+                |_env, _args| {
+                    Ok(Value::new(Bit::new(true)))
+                }
+            ).into()),
+            Op::Neq => Some(BuiltinFn::new(
+                "null_neq",
+                Type::named("bit"),
+                vec![Type::named("null"), Type::named("null")],
+                |_env, _args| {
+                    Ok(Value::new(Bit::new(false)))
+                }
+            ).into()),
+            _ => None,
+        }
     }
 }
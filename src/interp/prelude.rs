@@ -0,0 +1,106 @@
+use std::{fs, io};
+
+use super::{BuiltinFn, CharArray, Env, ExceptionKind, File, Fn, Int, Result, Type, Value};
+
+mod array;
+mod char_array;
+mod convert;
+mod file;
+mod process;
+mod read;
+mod union;
+
+/// Convert an int argument to a position within a sequence of length `len`, where `len` itself is
+/// the position just past the end
+fn bound(idx: &Value<'_>, len: usize) -> Result<usize> {
+    let idx = idx.downcast::<Int>()?.val();
+    usize::try_from(idx)
+        .ok()
+        .filter(|i| *i <= len)
+        .ok_or_else(|| ExceptionKind::IndexOutOfRange(idx, len).into())
+}
+
+/// Install the global variables and functions every program starts with
+pub(crate) fn install(env: &mut Env<'_>) {
+    // Push global variables
+    env.insert_var("int", Value::new(Type::named("int")));
+    env.insert_var("bigint", Value::new(Type::named("bigint")));
+    // Push global functions
+    env.insert_var(
+        "write_console",
+        Value::new::<Fn<'_>>(BuiltinFn::variadic(
+            "write_console",
+            Type::named("null"),
+            |_env, args| {
+                let mut w = io::stdout();
+                for arg in args {
+                    arg.write(&mut w)?;
+                }
+                Ok(Value::null())
+            },
+        ).into()),
+    );
+    env.insert_var(
+        "write_error",
+        Value::new::<Fn<'_>>(BuiltinFn::variadic(
+            "write_error",
+            Type::named("null"),
+            |_env, args| {
+                let mut w = io::stderr();
+                for arg in args {
+                    arg.write(&mut w)?;
+                }
+                Ok(Value::null())
+            },
+        ).into()),
+    );
+    env.insert_var(
+        "write_honk",
+        Value::new::<Fn<'_>>(BuiltinFn::variadic(
+            "write_honk",
+            Type::named("null"),
+            |env, args| {
+                let mut w = env.honk().writer()?;
+                for arg in args {
+                    arg.write(&mut w)?;
+                }
+                Ok(Value::null())
+            },
+        ).into()),
+    );
+    env.insert_var(
+        "write_io",
+        Value::new::<Fn<'_>>(BuiltinFn::variadic(
+            "write_io",
+            Type::named("null"),
+            |_env, args: &[Value<'_>]| {
+                let write = |file: &mut fs::File| {
+                    for arg in &args[1..] {
+                        arg.write(file)?;
+                    }
+                    Ok(())
+                };
+
+                if let Ok(f) = args[0].downcast::<File>() {
+                    f.with(write)?;
+                } else if let Ok(i) = args[0].downcast::<Int>() {
+                    File::from_fd(i.val())?.with(write)?;
+                } else if let Ok(s) = args[0].downcast::<CharArray>() {
+                    write(&mut fs::File::options().create(true).append(true).open(&**s)?)?;
+                } else {
+                    return Err(ExceptionKind::InvalidType(Type::named("file"), args[0].ty()).into());
+                }
+
+                Ok(Value::null())
+            },
+        ).into()),
+    );
+
+    array::install(env);
+    char_array::install(env);
+    convert::install(env);
+    file::install(env);
+    process::install(env);
+    read::install(env);
+    union::install(env);
+}
@@ -0,0 +1,156 @@
+use super::bound;
+use super::super::{Array, Bit, BuiltinFn, Env, ExceptionKind, Fn, Int, Result, Type, ValItem, Value};
+
+fn any_array() -> Type {
+    Type::Array(Box::new(Type::named("any")))
+}
+
+fn any_fn(ret: Type, args: usize) -> Type {
+    Type::Fn(Box::new(ret), vec![Type::named("any"); args])
+}
+
+/// The type of `arr`'s elements, or `None` for an empty array, whose elements could be anything
+fn elem_ty(arr: &Array<'_>) -> Option<Type> {
+    match arr.ty() {
+        Type::Array(elem) if !arr.0.is_empty() => Some(*elem),
+        _ => None,
+    }
+}
+
+/// Check that a value of type `ty` can be stored alongside the elements of `arr`. Empty arrays can
+/// hold anything.
+fn check_elem(arr: &Array<'_>, ty: Type) -> Result<()> {
+    match elem_ty(arr) {
+        Some(elem) if !elem.agrees(&ty) => Err(ExceptionKind::InvalidType(elem, ty).into()),
+        _ => Ok(()),
+    }
+}
+
+/// Check the types of what a builtin will pass `f` against its parameters, before calling it on
+/// anything, so a mismatch is reported where the builtin is called. `None` is an argument whose
+/// type isn't known, like the elements of an empty array. Gives `f`'s return type.
+fn check_params(env: &Env<'_>, f: &Fn<'_>, args: &[Option<Type>]) -> Result<Type> {
+    let (ret, params) = match f.ty().resolve(env) {
+        Type::Fn(ret, params) => (*ret, params),
+        ty => unreachable!("Function with non-function type {:?}", ty),
+    };
+    for (param, arg) in params.iter().zip(args) {
+        if let Some(arg) = arg.as_ref().filter(|arg| !param.matches(arg)) {
+            return Err(ExceptionKind::InvalidType(Clone::clone(param), Clone::clone(arg)).into());
+        }
+    }
+    Ok(ret)
+}
+
+pub(super) fn install(env: &mut Env<'_>) {
+    let fns = [
+        BuiltinFn::new("len", Type::named("int"), vec![any_array()], |_env, args| {
+            let arr = args[0].downcast::<Array<'_>>()?;
+            Ok(Value::new(Int::new(arr.0.len() as i128)))
+        }),
+        BuiltinFn::new("push", any_array(), vec![any_array(), Type::named("any")], |_env, args| {
+            let arr = args[0].downcast::<Array<'_>>()?;
+            check_elem(arr, args[1].ty())?;
+            let mut vals = arr.0.clone();
+            vals.push(args[1].clone());
+            Ok(Value::new(Array(vals)))
+        }),
+        BuiltinFn::new("pop", any_array(), vec![any_array()], |_env, args| {
+            let arr = args[0].downcast::<Array<'_>>()?;
+            match arr.0.split_last() {
+                Some((_, rest)) => Ok(Value::new(Array(rest.to_vec()))),
+                None => Err(ExceptionKind::EmptyArray(String::from("pop")).into()),
+            }
+        }),
+        BuiltinFn::new(
+            "slice",
+            any_array(),
+            vec![any_array(), Type::named("int"), Type::named("int")],
+            |_env, args| {
+                let arr = args[0].downcast::<Array<'_>>()?;
+                let end = bound(&args[2], arr.0.len())?;
+                let start = bound(&args[1], end)?;
+                Ok(Value::new(Array(arr.0[start..end].to_vec())))
+            },
+        ),
+        BuiltinFn::new("concat", any_array(), vec![any_array(), any_array()], |_env, args| {
+            let a = args[0].downcast::<Array<'_>>()?;
+            let b = args[1].downcast::<Array<'_>>()?;
+            if let Some(elem) = elem_ty(b) {
+                check_elem(a, elem)?;
+            }
+            Ok(Value::new(Array(a.0.iter().chain(&b.0).cloned().collect())))
+        }),
+        BuiltinFn::new("reverse", any_array(), vec![any_array()], |_env, args| {
+            let arr = args[0].downcast::<Array<'_>>()?;
+            Ok(Value::new(Array(arr.0.iter().rev().cloned().collect())))
+        }),
+        BuiltinFn::new(
+            "contains",
+            Type::named("bit"),
+            vec![any_array(), Type::named("any")],
+            |env, args| {
+                let arr = args[0].downcast::<Array<'_>>()?;
+                check_elem(arr, args[1].ty())?;
+                Ok(Value::new(Bit::new(arr.contains(env, &args[1])?)))
+            },
+        ),
+        BuiltinFn::new(
+            "map",
+            any_array(),
+            vec![any_array(), any_fn(Type::named("any"), 1)],
+            |env, args| {
+                let arr = args[0].downcast::<Array<'_>>()?;
+                let f = args[1].downcast::<Fn<'_>>()?;
+                check_params(env, f, &[elem_ty(arr)])?;
+                let vals = arr
+                    .0
+                    .iter()
+                    .map(|val| f.invoke(env, vec![val.clone()]))
+                    .collect::<Result<Vec<_>>>()?;
+                Ok(Value::new(Array(vals)))
+            },
+        ),
+        BuiltinFn::new(
+            "filter",
+            any_array(),
+            vec![any_array(), any_fn(Type::named("bit"), 1)],
+            |env, args| {
+                let arr = args[0].downcast::<Array<'_>>()?;
+                let f = args[1].downcast::<Fn<'_>>()?;
+                check_params(env, f, &[elem_ty(arr)])?;
+                let mut vals = Vec::new();
+                for val in &arr.0 {
+                    if f.invoke(env, vec![val.clone()])?.downcast::<Bit>()?.val() {
+                        vals.push(val.clone());
+                    }
+                }
+                Ok(Value::new(Array(vals)))
+            },
+        ),
+        BuiltinFn::new(
+            "fold",
+            Type::named("any"),
+            vec![any_array(), Type::named("any"), any_fn(Type::named("any"), 2)],
+            |env, args| {
+                let arr = args[0].downcast::<Array<'_>>()?;
+                let f = args[2].downcast::<Fn<'_>>()?;
+                // What `f` returns becomes the next accumulator, so it has to fit there too
+                let ret = check_params(env, f, &[Some(args[1].ty()), elem_ty(arr)])?;
+                if !ret.matches(&args[1].ty()) {
+                    return Err(ExceptionKind::InvalidType(ret, args[1].ty()).into());
+                }
+                let mut acc = args[1].clone();
+                for val in &arr.0 {
+                    acc = f.invoke(env, vec![acc, val.clone()])?;
+                }
+                Ok(acc)
+            },
+        ),
+    ];
+
+    for f in fns {
+        let name = f.name().to_string();
+        env.insert_var(&name, Value::new::<Fn<'_>>(f.into()));
+    }
+}
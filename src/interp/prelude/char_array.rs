@@ -0,0 +1,173 @@
+use super::bound;
+use super::super::{Array, Bit, BuiltinFn, Char, CharArray, Env, Fn, Int, Result, Type, Value};
+
+fn chararray() -> Type {
+    Type::named("chararray")
+}
+
+fn text<'ip>(s: String) -> Value<'ip> {
+    Value::new(CharArray::new(s))
+}
+
+/// The byte offset of the char at position `idx`, which must be within the string or just past it
+fn offset(s: &str, idx: usize) -> usize {
+    s.char_indices().nth(idx).map_or(s.len(), |(offset, _)| offset)
+}
+
+/// Collect an array of chararrays into strings
+fn strings(val: &Value<'_>) -> Result<Vec<String>> {
+    val.downcast::<Array<'_>>()?
+        .0
+        .iter()
+        .map(|val| Ok(val.downcast::<CharArray>()?.to_string()))
+        .collect()
+}
+
+pub(super) fn install(env: &mut Env<'_>) {
+    let fns = [
+        // `len` and `concat` only take arrays, so chararrays have their own
+        BuiltinFn::new("chararray_len", Type::named("int"), vec![chararray()], |_env, args| {
+            let s = args[0].downcast::<CharArray>()?;
+            Ok(Value::new(Int::new(s.chars().count() as i128)))
+        }),
+        BuiltinFn::new(
+            "chararray_concat",
+            chararray(),
+            vec![chararray(), chararray()],
+            |_env, args| {
+                let a = args[0].downcast::<CharArray>()?;
+                let b = args[1].downcast::<CharArray>()?;
+                Ok(text(format!("{}{}", &**a, &**b)))
+            },
+        ),
+        BuiltinFn::new(
+            "substring",
+            chararray(),
+            vec![chararray(), Type::named("int"), Type::named("int")],
+            |_env, args| {
+                let s = args[0].downcast::<CharArray>()?;
+                let end = bound(&args[2], s.chars().count())?;
+                let start = bound(&args[1], end)?;
+                Ok(text(s[offset(s, start)..offset(s, end)].to_string()))
+            },
+        ),
+        BuiltinFn::new(
+            "char_at",
+            Type::named("char"),
+            vec![chararray(), Type::named("int")],
+            |_env, args| args[0].get_index(args[1].downcast::<Int>()?.val()),
+        ),
+        BuiltinFn::new(
+            "split",
+            Type::Array(Box::new(chararray())),
+            vec![chararray(), chararray()],
+            |_env, args| {
+                let s = args[0].downcast::<CharArray>()?;
+                let sep = args[1].downcast::<CharArray>()?;
+                // An empty separator would match between every char, so split into chars instead
+                let parts = if sep.is_empty() {
+                    s.chars().map(|c| text(c.to_string())).collect()
+                } else {
+                    s.split(&**sep).map(|part| text(part.to_string())).collect()
+                };
+                Ok(Value::new(Array(parts)))
+            },
+        ),
+        BuiltinFn::new(
+            "join",
+            chararray(),
+            vec![Type::Array(Box::new(Type::named("any"))), chararray()],
+            |_env, args| {
+                let parts = strings(&args[0])?;
+                let sep = args[1].downcast::<CharArray>()?;
+                Ok(text(parts.join(sep)))
+            },
+        ),
+        BuiltinFn::new("trim", chararray(), vec![chararray()], |_env, args| {
+            Ok(text(args[0].downcast::<CharArray>()?.trim().to_string()))
+        }),
+        BuiltinFn::new("upper", chararray(), vec![chararray()], |_env, args| {
+            Ok(text(args[0].downcast::<CharArray>()?.to_uppercase()))
+        }),
+        BuiltinFn::new("lower", chararray(), vec![chararray()], |_env, args| {
+            Ok(text(args[0].downcast::<CharArray>()?.to_lowercase()))
+        }),
+        BuiltinFn::new(
+            "find",
+            Type::named("int"),
+            vec![chararray(), chararray()],
+            |_env, args| {
+                let s = args[0].downcast::<CharArray>()?;
+                let needle = args[1].downcast::<CharArray>()?;
+                // Positions count chars, like indexing does, and -1 means not found
+                let idx = s
+                    .find(&**needle)
+                    .map_or(-1, |offset| s[..offset].chars().count() as i128);
+                Ok(Value::new(Int::new(idx)))
+            },
+        ),
+        BuiltinFn::new(
+            "replace",
+            chararray(),
+            vec![chararray(), chararray(), chararray()],
+            |_env, args| {
+                let s = args[0].downcast::<CharArray>()?;
+                let from = args[1].downcast::<CharArray>()?;
+                let to = args[2].downcast::<CharArray>()?;
+                // An empty pattern would match between every char, so there's nothing to replace
+                if from.is_empty() {
+                    return Ok(text(s.to_string()));
+                }
+                Ok(text(s.replace(&**from, to)))
+            },
+        ),
+        BuiltinFn::new(
+            "starts_with",
+            Type::named("bit"),
+            vec![chararray(), chararray()],
+            |_env, args| {
+                let s = args[0].downcast::<CharArray>()?;
+                let prefix = args[1].downcast::<CharArray>()?;
+                Ok(Value::new(Bit::new(s.starts_with(&**prefix))))
+            },
+        ),
+        BuiltinFn::new(
+            "ends_with",
+            Type::named("bit"),
+            vec![chararray(), chararray()],
+            |_env, args| {
+                let s = args[0].downcast::<CharArray>()?;
+                let suffix = args[1].downcast::<CharArray>()?;
+                Ok(Value::new(Bit::new(s.ends_with(&**suffix))))
+            },
+        ),
+        BuiltinFn::new(
+            "chars",
+            Type::Array(Box::new(Type::named("char"))),
+            vec![chararray()],
+            |_env, args| {
+                let s = args[0].downcast::<CharArray>()?;
+                Ok(Value::new(Array(s.chars().map(|c| Value::new(Char::new(c))).collect())))
+            },
+        ),
+        BuiltinFn::new(
+            "from_chars",
+            chararray(),
+            vec![Type::Array(Box::new(Type::named("any")))],
+            |_env, args| {
+                let s = args[0]
+                    .downcast::<Array<'_>>()?
+                    .0
+                    .iter()
+                    .map(|val| Ok(val.downcast::<Char>()?.val()))
+                    .collect::<Result<String>>()?;
+                Ok(text(s))
+            },
+        ),
+    ];
+
+    for f in fns {
+        let name = f.name().to_string();
+        env.insert_var(&name, Value::new::<Fn<'_>>(f.into()));
+    }
+}
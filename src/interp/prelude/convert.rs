@@ -0,0 +1,147 @@
+use num_traits::{FromPrimitive, ToPrimitive};
+
+use super::super::{
+    BigInt, Bit, BuiltinFn, Char, CharArray, Env, Exception, ExceptionKind, Float, Fn, Int,
+    Result, Type, Value,
+};
+
+/// The exception for a value which has no conversion to `to`
+fn invalid(val: &Value<'_>, to: &str) -> Exception {
+    ExceptionKind::InvalidConversion(val.ty(), Type::named(to)).into()
+}
+
+/// The exception for text which doesn't hold a valid `to`
+fn unparsable(text: &str, to: &str) -> Exception {
+    ExceptionKind::ParseFailed(text.to_string(), Type::named(to)).into()
+}
+
+fn out_of_range(to: &str) -> Exception {
+    ExceptionKind::OutOfRange(Type::named(to)).into()
+}
+
+fn to_int(val: &Value<'_>) -> Result<i128> {
+    if let Ok(i) = val.downcast::<Int>() {
+        Ok(i.val())
+    } else if let Ok(i) = val.downcast::<BigInt>() {
+        i.to_i128().ok_or_else(|| out_of_range("int"))
+    } else if let Ok(f) = val.downcast::<Float>() {
+        // Truncates towards zero, NaN and infinities don't fit
+        f.val().to_i128().ok_or_else(|| out_of_range("int"))
+    } else if let Ok(b) = val.downcast::<Bit>() {
+        Ok(b.val() as i128)
+    } else if let Ok(c) = val.downcast::<Char>() {
+        Ok(c.val() as i128)
+    } else if let Ok(s) = val.downcast::<CharArray>() {
+        s.parse().map_err(|_| unparsable(s, "int"))
+    } else {
+        Err(invalid(val, "int"))
+    }
+}
+
+fn to_bigint(val: &Value<'_>) -> Result<num_bigint::BigInt> {
+    if let Ok(i) = val.downcast::<BigInt>() {
+        Ok(i.val().clone())
+    } else if let Ok(f) = val.downcast::<Float>() {
+        num_bigint::BigInt::from_f64(f.val().trunc()).ok_or_else(|| out_of_range("bigint"))
+    } else if let Ok(s) = val.downcast::<CharArray>() {
+        // Bigint literals end in `n`, so allow it here too
+        s.strip_suffix('n')
+            .unwrap_or(s)
+            .parse()
+            .map_err(|_| unparsable(s, "bigint"))
+    } else if val.downcast::<Int>().is_ok()
+        || val.downcast::<Bit>().is_ok()
+        || val.downcast::<Char>().is_ok()
+    {
+        to_int(val).map(Into::into)
+    } else {
+        Err(invalid(val, "bigint"))
+    }
+}
+
+fn to_float(val: &Value<'_>) -> Result<f64> {
+    if let Ok(f) = val.downcast::<Float>() {
+        Ok(f.val())
+    } else if let Ok(i) = val.downcast::<Int>() {
+        Ok(i.val() as f64)
+    } else if let Ok(i) = val.downcast::<BigInt>() {
+        i.to_f64().filter(|f| f.is_finite()).ok_or_else(|| out_of_range("float"))
+    } else if let Ok(b) = val.downcast::<Bit>() {
+        Ok(if b.val() { 1.0 } else { 0.0 })
+    } else if let Ok(s) = val.downcast::<CharArray>() {
+        s.parse().map_err(|_| unparsable(s, "float"))
+    } else {
+        Err(invalid(val, "float"))
+    }
+}
+
+fn to_bit(val: &Value<'_>) -> Result<bool> {
+    if let Ok(b) = val.downcast::<Bit>() {
+        Ok(b.val())
+    } else if let Ok(i) = val.downcast::<Int>() {
+        match i.val() {
+            0 => Ok(false),
+            1 => Ok(true),
+            _ => Err(out_of_range("bit")),
+        }
+    } else if let Ok(s) = val.downcast::<CharArray>() {
+        match &**s {
+            "0b" => Ok(false),
+            "1b" => Ok(true),
+            _ => Err(unparsable(s, "bit")),
+        }
+    } else {
+        Err(invalid(val, "bit"))
+    }
+}
+
+fn to_char(val: &Value<'_>) -> Result<char> {
+    if let Ok(c) = val.downcast::<Char>() {
+        Ok(c.val())
+    } else if let Ok(i) = val.downcast::<Int>() {
+        // Surrogates and anything past the last code point aren't chars
+        u32::try_from(i.val())
+            .ok()
+            .and_then(char::from_u32)
+            .ok_or_else(|| out_of_range("char"))
+    } else if let Ok(s) = val.downcast::<CharArray>() {
+        let mut chars = s.chars();
+        match (chars.next(), chars.next()) {
+            (Some(c), None) => Ok(c),
+            _ => Err(unparsable(s, "char")),
+        }
+    } else {
+        Err(invalid(val, "char"))
+    }
+}
+
+pub(super) fn install(env: &mut Env<'_>) {
+    let any = || vec![Type::named("any")];
+    let fns = [
+        BuiltinFn::new("to_int", Type::named("int"), any(), |_env, args| {
+            Ok(Value::new(Int::new(to_int(&args[0])?)))
+        }),
+        BuiltinFn::new("to_bigint", Type::named("bigint"), any(), |_env, args| {
+            Ok(Value::new(BigInt::new(to_bigint(&args[0])?)))
+        }),
+        BuiltinFn::new("to_float", Type::named("float"), any(), |env, args| {
+            Float::strict(env, to_float(&args[0])?, || String::from("`to_float`"))
+        }),
+        BuiltinFn::new("to_bit", Type::named("bit"), any(), |_env, args| {
+            Ok(Value::new(Bit::new(to_bit(&args[0])?)))
+        }),
+        BuiltinFn::new("to_char", Type::named("char"), any(), |_env, args| {
+            Ok(Value::new(Char::new(to_char(&args[0])?)))
+        }),
+        BuiltinFn::new("to_chararray", Type::named("chararray"), any(), |_env, args| {
+            let mut buf = Vec::new();
+            args[0].write(&mut buf)?;
+            Ok(Value::new(CharArray::new(String::from_utf8_lossy(&buf).into_owned())))
+        }),
+    ];
+
+    for f in fns {
+        let name = f.name().to_string();
+        env.insert_var(&name, Value::new::<Fn<'_>>(f.into()));
+    }
+}
@@ -0,0 +1,47 @@
+use std::io::{Seek, SeekFrom, Write};
+
+use super::super::{BuiltinFn, CharArray, Env, ExceptionKind, File, Fn, Int, Type, Value};
+
+pub(super) fn install(env: &mut Env<'_>) {
+    let file = || Type::named("file");
+    let fns = [
+        BuiltinFn::new(
+            "open",
+            file(),
+            vec![Type::named("chararray"), Type::named("chararray")],
+            |_env, args| {
+                let path = args[0].downcast::<CharArray>()?;
+                let mode = args[1].downcast::<CharArray>()?;
+                Ok(Value::new(File::open(path, mode)?))
+            },
+        ),
+        BuiltinFn::new("close", Type::named("null"), vec![file()], |_env, args| {
+            args[0].downcast::<File>()?.close()?;
+            Ok(Value::null())
+        }),
+        BuiltinFn::new("flush", Type::named("null"), vec![file()], |_env, args| {
+            args[0].downcast::<File>()?.with(|f| Ok(f.flush()?))?;
+            Ok(Value::null())
+        }),
+        BuiltinFn::new(
+            "seek",
+            Type::named("int"),
+            vec![file(), Type::named("int")],
+            |_env, args| {
+                let pos = args[1].downcast::<Int>()?.val();
+                let pos = u64::try_from(pos).map_err(|_| ExceptionKind::OutOfRange(Type::named("int")))?;
+                let pos = args[0].downcast::<File>()?.with(|f| Ok(f.seek(SeekFrom::Start(pos))?))?;
+                Ok(Value::new(Int::new(pos.into())))
+            },
+        ),
+        BuiltinFn::new("tell", Type::named("int"), vec![file()], |_env, args| {
+            let pos = args[0].downcast::<File>()?.with(|f| Ok(f.stream_position()?))?;
+            Ok(Value::new(Int::new(pos.into())))
+        }),
+    ];
+
+    for f in fns {
+        let name = f.name().to_string();
+        env.insert_var(&name, Value::new::<Fn<'_>>(f.into()));
+    }
+}
@@ -0,0 +1,57 @@
+use std::env;
+
+use super::super::{Bit, BuiltinFn, CharArray, Env, ExceptionKind, Fn, Int, Type, Value};
+
+/// Whether the OS would accept `name` and `val` as an environment variable
+fn valid_var(name: &str, val: &str) -> bool {
+    !name.is_empty() && !name.contains(['=', '\0']) && !val.contains('\0')
+}
+
+pub(super) fn install(env: &mut Env<'_>) {
+    let fns = [
+        BuiltinFn::new(
+            "get_env",
+            Type::named("chararray"),
+            vec![Type::named("chararray")],
+            |_env, args| {
+                let name = args[0].downcast::<CharArray>()?;
+                let val = env::var_os(&**name)
+                    .ok_or_else(|| ExceptionKind::EnvVarNotFound(name.to_string()))?;
+                Ok(Value::new(CharArray::new(val.to_string_lossy().into_owned())))
+            },
+        ),
+        BuiltinFn::new(
+            "has_env",
+            Type::named("bit"),
+            vec![Type::named("chararray")],
+            |_env, args| {
+                let name = args[0].downcast::<CharArray>()?;
+                Ok(Value::new(Bit::new(env::var_os(&**name).is_some())))
+            },
+        ),
+        BuiltinFn::new(
+            "set_env",
+            Type::named("null"),
+            vec![Type::named("chararray"), Type::named("chararray")],
+            |_env, args| {
+                let name = args[0].downcast::<CharArray>()?;
+                let val = args[1].downcast::<CharArray>()?;
+                if !valid_var(name, val) {
+                    return Err(ExceptionKind::InvalidEnvVar(name.to_string()).into());
+                }
+                env::set_var(&**name, &**val);
+                Ok(Value::null())
+            },
+        ),
+        BuiltinFn::new("exit", Type::named("null"), vec![Type::named("int")], |_env, args| {
+            let status = args[0].downcast::<Int>()?.val();
+            let status = u8::try_from(status).map_err(|_| ExceptionKind::InvalidExitStatus(status))?;
+            Err(ExceptionKind::Exit(status).into())
+        }),
+    ];
+
+    for f in fns {
+        let name = f.name().to_string();
+        env.insert_var(&name, Value::new::<Fn<'_>>(f.into()));
+    }
+}
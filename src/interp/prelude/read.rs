@@ -0,0 +1,125 @@
+use std::io::{self, Read};
+use std::fs;
+
+use super::super::{
+    BuiltinFn, CharArray, Env, Exception, ExceptionKind, File, Fn, Int, Result, Type, Value,
+};
+
+/// How much to take from a source, parsed from the last argument of a read builtin
+enum Amount {
+    Line,
+    All,
+    Count(usize),
+}
+
+impl Amount {
+    fn from_value(val: &Value<'_>) -> Result<Amount> {
+        if let Ok(i) = val.downcast::<Int>() {
+            usize::try_from(i.val())
+                .map(Amount::Count)
+                .map_err(|_| ExceptionKind::InvalidReadAmount(i.val().to_string()).into())
+        } else if let Ok(s) = val.downcast::<CharArray>() {
+            match &**s {
+                "line" => Ok(Amount::Line),
+                "all" => Ok(Amount::All),
+                _ => Err(ExceptionKind::InvalidReadAmount(format!("{:?}", &**s)).into()),
+            }
+        } else {
+            Err(ExceptionKind::InvalidType(Type::named("int"), val.ty()).into())
+        }
+    }
+}
+
+/// Read a single byte, or `None` at the end of input
+fn byte(r: &mut dyn Read) -> io::Result<Option<u8>> {
+    let mut buf = [0];
+    loop {
+        match r.read(&mut buf) {
+            Ok(0) => return Ok(None),
+            Ok(_) => return Ok(Some(buf[0])),
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+/// Read `amount` from `r`. Reading goes a byte at a time, so nothing past the amount is taken
+/// from a source that's read again later, such as stdin or an fd. Lines keep their newline, so
+/// the empty chararray only comes back once the input has ended.
+fn read<'ip>(r: &mut dyn Read, amount: Amount) -> Result<Value<'ip>> {
+    let mut bytes = Vec::new();
+    match amount {
+        Amount::All => {
+            r.read_to_end(&mut bytes)?;
+        }
+        Amount::Line => {
+            while let Some(b) = byte(r)? {
+                bytes.push(b);
+                if b == b'\n' {
+                    break;
+                }
+            }
+        }
+        Amount::Count(count) => {
+            for _ in 0..count {
+                let first = match byte(r)? {
+                    Some(b) => b,
+                    None => break,
+                };
+                bytes.push(first);
+                // The leading byte of a UTF-8 char says how many more belong to it
+                let rest = (first.leading_ones() as usize).saturating_sub(1);
+                for _ in 0..rest {
+                    match byte(r)? {
+                        Some(b) => bytes.push(b),
+                        None => break,
+                    }
+                }
+            }
+        }
+    }
+
+    let text = String::from_utf8(bytes).map_err(|_| Exception::from(ExceptionKind::Io))?;
+    Ok(Value::new(CharArray::new(text)))
+}
+
+pub(super) fn install(env: &mut Env<'_>) {
+    let fns = [
+        BuiltinFn::new(
+            "read_console",
+            Type::named("chararray"),
+            vec![Type::named("any")],
+            |_env, args| {
+                let amount = Amount::from_value(&args[0])?;
+                read(&mut io::stdin().lock(), amount)
+            },
+        ),
+        BuiltinFn::new(
+            "read_io",
+            Type::named("chararray"),
+            vec![Type::named("any"), Type::named("any")],
+            |_env, args| {
+                let amount = Amount::from_value(&args[1])?;
+                if let Ok(f) = args[0].downcast::<File>() {
+                    f.with(|file| read(file, amount))
+                } else if let Ok(i) = args[0].downcast::<Int>() {
+                    // Stdin is buffered, so reading fd 0 directly would miss what's already in
+                    // the buffer
+                    if i.val() == 0 {
+                        return read(&mut io::stdin().lock(), amount);
+                    }
+                    File::from_fd(i.val())?.with(|file| read(file, amount))
+                } else if let Ok(s) = args[0].downcast::<CharArray>() {
+                    read(&mut io::BufReader::new(fs::File::open(&**s)?), amount)
+                } else {
+                    Err(ExceptionKind::InvalidType(Type::named("file"), args[0].ty()).into())
+                }
+            },
+        ),
+    ];
+
+    for f in fns {
+        let name = f.name().to_string();
+        env.insert_var(&name, Value::new::<Fn<'_>>(f.into()));
+    }
+}
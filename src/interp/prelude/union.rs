@@ -0,0 +1,29 @@
+use super::super::{BuiltinFn, Bit, Env, Fn, Type, Union, Value};
+
+pub(super) fn install(env: &mut Env<'_>) {
+    let fns = [
+        BuiltinFn::new(
+            "is",
+            Type::named("bit"),
+            vec![Type::named("any"), Type::named("any")],
+            |_env, args| {
+                let val = args[0].downcast::<Union<'_>>()?;
+                Ok(Value::new(Bit::new(val.is(args[1].downcast::<Fn<'_>>()?)?)))
+            },
+        ),
+        BuiltinFn::new(
+            "payload",
+            Type::named("any"),
+            vec![Type::named("any"), Type::named("any")],
+            |_env, args| {
+                let val = args[0].downcast::<Union<'_>>()?;
+                val.payload(args[1].downcast::<Fn<'_>>()?)
+            },
+        ),
+    ];
+
+    for f in fns {
+        let name = f.name().to_string();
+        env.insert_var(&name, Value::new::<Fn<'_>>(f.into()));
+    }
+}
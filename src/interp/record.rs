@@ -0,0 +1,146 @@
+use std::io::{self, Write};
+use super::{ValItem, Value, Bit, Env, Fn, Result, BuiltinFn, ExceptionKind, Type, Op};
+use crate::ast::Ident;
+
+/// A value of a record type, holding its fields in the order they were declared
+pub struct Record<'ip> {
+    ty: Type,
+    fields: Vec<Value<'ip>>,
+}
+
+impl<'ip> Record<'ip> {
+    /// Build a record of type `ty` from named values, which must give every declared field once
+    pub fn new(env: &mut Env<'ip>, ty: Type, mut vals: Vec<(Ident, Value<'ip>)>) -> Result<Record<'ip>> {
+        let decl = match &ty {
+            Type::Record(_, decl) => decl,
+            _ => return Err(ExceptionKind::NotARecord(ty).into()),
+        };
+        if let Some((name, _)) = vals.iter().find(|(name, _)| decl.iter().all(|(f, _)| f != name)) {
+            return Err(ExceptionKind::FieldNotFound(Clone::clone(&ty), name.clone()).into());
+        }
+
+        let mut fields = Vec::with_capacity(decl.len());
+        for (name, field_ty) in decl {
+            let idx = vals
+                .iter()
+                .position(|(given, _)| given == name)
+                .ok_or_else(|| ExceptionKind::MissingField(Clone::clone(&ty), name.clone()))?;
+            let (_, val) = vals.swap_remove(idx);
+            field_ty.validate(env, &val)?;
+            fields.push(val);
+        }
+
+        Ok(Record { ty, fields })
+    }
+
+    /// Where a field is stored, along with the type it was declared with
+    fn field(&self, name: &str) -> Option<(usize, &Type)> {
+        match &self.ty {
+            Type::Record(_, decl) => decl
+                .iter()
+                .enumerate()
+                .find(|(_, (f, _))| &**f == name)
+                .map(|(idx, (_, ty))| (idx, ty)),
+            _ => None,
+        }
+    }
+
+    /// Compare two records field by field, using the fields' own equality
+    fn equals(&self, env: &mut Env<'ip>, other: &Record<'ip>) -> Result<bool> {
+        for (a, b) in self.fields.iter().zip(&other.fields) {
+            let eq = a
+                .get_op(Op::Eq)
+                .ok_or_else(|| ExceptionKind::InvalidOp(Op::Eq, a.ty(), Some(b.ty())))?
+                .invoke(env, vec![a.clone(), b.clone()])?;
+            if !eq.downcast::<Bit>()?.val() {
+                return Ok(false);
+            }
+        }
+        Ok(true)
+    }
+}
+
+unsafe impl<'ip> ValItem<'ip> for Record<'ip> {
+    fn allow_cast(ty: Type) -> Result<()> {
+        if let Type::Record(_, _) = ty {
+            Ok(())
+        } else {
+            Err(ExceptionKind::NotARecord(ty).into())
+        }
+    }
+
+    fn clone(&self) -> Box<dyn ValItem<'ip> + 'ip> {
+        Box::new(Record {
+            ty: Clone::clone(&self.ty),
+            fields: self.fields.clone(),
+        })
+    }
+
+    fn ty(&self) -> Type {
+        Clone::clone(&self.ty)
+    }
+
+    fn write(&self, w: &mut dyn Write) -> io::Result<()> {
+        let decl = match &self.ty {
+            Type::Record(_, decl) => decl,
+            _ => unreachable!("Record with non-record type {:?}", self.ty),
+        };
+        write!(w, "{} {{ ", self.ty.pretty())?;
+        for (idx, ((name, _), val)) in decl.iter().zip(&self.fields).enumerate() {
+            if idx != 0 {
+                write!(w, ", ")?;
+            }
+            write!(w, "{}: ", &**name)?;
+            val.data.write(w)?;
+        }
+        write!(w, " }}")
+    }
+
+    fn get_field(&self, name: &str) -> Option<Value<'ip>> {
+        self.field(name).map(|(idx, _)| self.fields[idx].clone())
+    }
+
+    fn set_field(&self, env: &mut Env<'ip>, name: &str, val: Value<'ip>) -> Result<Value<'ip>> {
+        let (idx, field_ty) = self
+            .field(name)
+            .ok_or_else(|| ExceptionKind::FieldNotFound(self.ty(), Ident(name.to_string())))?;
+        // A field keeps the type it was declared with, whatever it holds right now
+        field_ty.validate(env, &val)?;
+
+        let mut fields = self.fields.clone();
+        fields[idx] = val;
+        Ok(Value::new(Record {
+            ty: Clone::clone(&self.ty),
+            fields,
+        }))
+    }
+
+    fn get_op(&self, op: Op) -> Option<Fn<'ip>> {
+        let ty = ValItem::ty(self);
+        match op {
+            Op::Eq => Some(BuiltinFn::new(
+                "record_eq",
+                Type::named("bit"),
+                vec![Clone::clone(&ty), ty],
+                |env, args| {
+                    let a = args[0].downcast::<Record<'_>>()?;
+                    let b = args[1].downcast::<Record<'_>>()?;
+
+                    Ok(Value::new(Bit::new(a.equals(env, b)?)))
+                }
+            ).into()),
+            Op::Neq => Some(BuiltinFn::new(
+                "record_neq",
+                Type::named("bit"),
+                vec![Clone::clone(&ty), ty],
+                |env, args| {
+                    let a = args[0].downcast::<Record<'_>>()?;
+                    let b = args[1].downcast::<Record<'_>>()?;
+
+                    Ok(Value::new(Bit::new(!a.equals(env, b)?)))
+                }
+            ).into()),
+            _ => None,
+        }
+    }
+}
@@ -1,32 +1,14 @@
-// use crate::ast::Type;
-use super::{ValItem, Value, Fn, Exception, Result, Op, Env};
-use once_cell::sync::OnceCell;
-
-#[derive(Clone, Debug)]
-pub struct Type {
-    id: u64,
-    name: String,
-}
-
-impl Type {
-    pub(super) fn new(id: u64, name: String) -> Type {
-        Type {
-            id,
-            name,
-        }
-    }
-
-    pub fn new_type<'ip>(env: &mut Env<'ip>, name: &str) -> Type {
-        env.new_ty(name)
-    }
-}
+use std::io::{self, Write};
+pub use crate::ast::Type;
+use super::{ValItem, Value, Bit, Fn, BuiltinFn, ExceptionKind, Result, Op, Env};
+use crate::ast::Ident;
 
 unsafe impl<'ip> ValItem<'ip> for Type {
     fn allow_cast(ty: Type) -> Result<()> {
         if ty == Type::named("type") {
             Ok(())
         } else {
-            Err(Exception::InvalidType(Type::named("type"), ty))
+            Err(ExceptionKind::InvalidType(Type::named("type"), ty).into())
         }
     }
 
@@ -34,10 +16,12 @@ unsafe impl<'ip> ValItem<'ip> for Type {
         Box::new(Clone::clone(self))
     }
 
-    fn ty(&self, env: &mut Env<'_>) -> Type {
-        static TY: OnceCell<Type> = OnceCell::new();
+    fn ty(&self) -> Type {
+        Type::named("type")
+    }
 
-        Clone::clone(TY.get_or_init(|| env.new_ty("int")))
+    fn write(&self, w: &mut dyn Write) -> io::Result<()> {
+        write!(w, "<type {}>", self.pretty())
     }
 
     fn get_field(&self, _name: &str) -> Option<Value<'ip>> {
@@ -46,6 +30,157 @@ unsafe impl<'ip> ValItem<'ip> for Type {
     }
 
     fn get_op(&self, op: Op) -> Option<Fn<'ip>> {
-        todo!()
+        match op {
+            Op::Eq => Some(BuiltinFn::new(
+                "type_eq",
+                Type::named("bit"),
+                vec![Type::named("type"), Type::named("type")],
+                |_env, args| {
+                    let a = args[0].downcast::<Type>()?;
+                    let b = args[1].downcast::<Type>()?;
+
+                    Ok(Value::new(Bit::new(a == b)))
+                }
+            ).into()),
+            Op::Neq => Some(BuiltinFn::new(
+                "type_neq",
+                Type::named("bit"),
+                vec![Type::named("type"), Type::named("type")],
+                |_env, args| {
+                    let a = args[0].downcast::<Type>()?;
+                    let b = args[1].downcast::<Type>()?;
+
+                    Ok(Value::new(Bit::new(a != b)))
+                }
+            ).into()),
+            _ => None,
+        }
+    }
+}
+
+impl Type {
+    /// Replace any names bound to other types with `type` by the types they stand for
+    pub fn resolve(&self, env: &Env<'_>) -> Type {
+        self.resolve_with(env, &mut Vec::new())
+    }
+
+    fn resolve_with(&self, env: &Env<'_>, expanding: &mut Vec<Ident>) -> Type {
+        match self {
+            // An alias can refer to itself, so stop expanding it the second time round
+            Type::Named(name) if !expanding.contains(name) => {
+                let alias = env.lookup_var(name);
+                match alias.as_ref().map(|val| val.downcast::<Type>()) {
+                    Some(Ok(ty)) if ty != self => {
+                        expanding.push(name.clone());
+                        let ty = ty.resolve_with(env, expanding);
+                        expanding.pop();
+                        ty
+                    }
+                    _ => Clone::clone(self),
+                }
+            }
+            Type::Named(_) => Clone::clone(self),
+            // Records are identified by their declaration, so their fields are left as written
+            Type::Record(_, _) | Type::Union(_, _) => Clone::clone(self),
+            Type::Array(inner) => Type::Array(Box::new(inner.resolve_with(env, expanding))),
+            Type::Fn(ret, args) => Type::Fn(
+                Box::new(ret.resolve_with(env, expanding)),
+                args.iter().map(|arg| arg.resolve_with(env, expanding)).collect(),
+            ),
+        }
+    }
+
+    pub fn validate<'ip>(&self, env: &mut Env<'ip>, val: &Value<'ip>) -> Result<()> {
+        self.validate_generic(env, val, &mut [])
+    }
+
+    /// Check a value against this type, where `generics` are the type parameters in scope. The
+    /// first value checked against a parameter binds it to that value's type, and later values
+    /// must agree with it. `any` accepts every type, without binding anything.
+    ///
+    /// A generic function's own type parameters are bound too, to the types expected of it, so
+    /// `fn<T>: T (x: T)` can be passed as a `fn: int (int)` but not as a `fn: int (chararray)`.
+    pub fn validate_generic<'ip>(
+        &self,
+        env: &mut Env<'ip>,
+        val: &Value<'ip>,
+        generics: &mut [(Ident, Option<Type>)],
+    ) -> Result<()> {
+        let (actual, mut theirs) = match val.downcast::<Fn<'_>>() {
+            Ok(Fn::User(proto, _)) if !proto.generics.is_empty() => {
+                let declared = Type::Fn(Box::new(Clone::clone(&proto.ret.node)), proto.arg_tys());
+                // The function's parameters stand for themselves, whatever they name out here
+                let actual = declared.resolve_with(env, &mut proto.generics.clone());
+                (actual, proto.generics.iter().map(|param| (param.clone(), None)).collect())
+            }
+            _ => (val.ty().resolve(env), Vec::new()),
+        };
+        if self.unify(env, &actual, generics, &mut theirs) {
+            Ok(())
+        } else {
+            // A generic function is described with its parameters, rather than as taking `any`
+            let actual = if theirs.is_empty() { val.ty() } else { actual };
+            Err(ExceptionKind::InvalidType(self.substitute(generics), actual).into())
+        }
+    }
+
+    /// Whether a value of type `actual` fits this type, binding the type parameters on either
+    /// side as it goes: `generics` are those of the type expected, and `theirs` those of a generic
+    /// function being checked against it
+    fn unify(
+        &self,
+        env: &Env<'_>,
+        actual: &Type,
+        generics: &mut [(Ident, Option<Type>)],
+        theirs: &mut [(Ident, Option<Type>)],
+    ) -> bool {
+        let any = Type::named("any");
+        if let Type::Named(name) = actual {
+            if let Some((_, bound)) = theirs.iter_mut().find(|(param, _)| param == name) {
+                return match bound {
+                    Some(bound) => {
+                        let bound = Clone::clone(bound);
+                        self.unify(env, &bound, generics, theirs)
+                    }
+                    None => {
+                        *bound = Some(self.substitute(generics));
+                        true
+                    }
+                };
+            }
+        }
+
+        match self {
+            Type::Named(name) if &**name == "any" => true,
+            Type::Named(name) => match generics.iter_mut().find(|(param, _)| param == name) {
+                Some((_, Some(bound))) => Clone::clone(bound).unify(env, actual, &mut [], theirs),
+                Some((_, unbound)) => {
+                    *unbound = Some(Clone::clone(actual));
+                    true
+                }
+                None => match self.resolve(env) {
+                    resolved if resolved == *self => resolved == *actual,
+                    resolved => resolved.unify(env, actual, generics, theirs),
+                },
+            },
+            Type::Array(inner) => match actual {
+                // An empty array fits every array type
+                Type::Array(actual) if **actual == any => true,
+                Type::Array(actual) => inner.unify(env, actual, generics, theirs),
+                _ => false,
+            },
+            Type::Fn(ret, args) => match actual {
+                Type::Fn(actual_ret, actual_args) => {
+                    ret.unify(env, actual_ret, generics, theirs)
+                        && args.len() == actual_args.len()
+                        // A function taking `any` accepts whatever it's expected to be passed
+                        && args.iter().zip(actual_args).all(|(arg, actual)| {
+                            *actual == any || arg.unify(env, actual, generics, theirs)
+                        })
+                }
+                _ => false,
+            },
+            Type::Record(_, _) | Type::Union(_, _) => self == actual,
+        }
     }
 }
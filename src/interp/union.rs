@@ -0,0 +1,148 @@
+use std::io::{self, Write};
+use super::{ValItem, Value, Bit, Env, Fn, Result, BuiltinFn, ExceptionKind, Type, Op};
+use crate::ast::Ident;
+
+/// A value of a union type, holding one of its variants and that variant's payload, if it has one
+pub struct Union<'ip> {
+    ty: Type,
+    variant: usize,
+    payload: Option<Value<'ip>>,
+}
+
+impl<'ip> Union<'ip> {
+    /// Construct the variant at index `variant` of `ty`, from the arguments given to its
+    /// constructor
+    pub fn new(env: &mut Env<'ip>, ty: Type, variant: usize, mut args: Vec<Value<'ip>>) -> Result<Union<'ip>> {
+        let expected = Union::payload_ty(&ty, variant).cloned();
+        let arity = usize::from(expected.is_some());
+        if args.len() != arity {
+            let name = Union::variants(&ty)[variant].0.clone();
+            return Err(ExceptionKind::InvalidArgCount(name, arity, args.len()).into());
+        }
+
+        let payload = args.pop();
+        if let (Some(expected), Some(payload)) = (&expected, &payload) {
+            expected.validate(env, payload)?;
+        }
+        Ok(Union { ty, variant, payload })
+    }
+
+    fn variants(ty: &Type) -> &[(Ident, Option<Type>)] {
+        match ty {
+            Type::Union(_, variants) => variants,
+            _ => unreachable!("Union with non-union type {:?}", ty),
+        }
+    }
+
+    pub(crate) fn variant_name(ty: &Type, variant: usize) -> &str {
+        &Union::variants(ty)[variant].0
+    }
+
+    pub(crate) fn payload_ty(ty: &Type, variant: usize) -> Option<&Type> {
+        Union::variants(ty)[variant].1.as_ref()
+    }
+
+    /// Whether this value holds the variant `constructor` builds, which must be of the same union
+    pub fn is(&self, constructor: &Fn<'ip>) -> Result<bool> {
+        match constructor.variant() {
+            Some((ty, _)) if *ty != self.ty => {
+                Err(ExceptionKind::InvalidType(Clone::clone(ty), Clone::clone(&self.ty)).into())
+            }
+            Some((_, variant)) => Ok(variant == self.variant),
+            None => Err(ExceptionKind::NotAVariant(Ident(constructor.name().to_string())).into()),
+        }
+    }
+
+    /// The payload of the variant `constructor` builds, or null if it has none. Raises if this
+    /// value holds a different variant.
+    pub fn payload(&self, constructor: &Fn<'ip>) -> Result<Value<'ip>> {
+        if !self.is(constructor)? {
+            let expected = Ident(constructor.name().to_string());
+            let actual = Union::variants(&self.ty)[self.variant].0.clone();
+            return Err(ExceptionKind::WrongVariant(expected, actual).into());
+        }
+        Ok(self.payload.clone().unwrap_or_else(Value::null))
+    }
+
+    /// Compare two values of the same union, using their payloads' own equality
+    fn equals(&self, env: &mut Env<'ip>, other: &Union<'ip>) -> Result<bool> {
+        if self.variant != other.variant {
+            return Ok(false);
+        }
+        match (&self.payload, &other.payload) {
+            (Some(a), Some(b)) => {
+                let eq = a
+                    .get_op(Op::Eq)
+                    .ok_or_else(|| ExceptionKind::InvalidOp(Op::Eq, a.ty(), Some(b.ty())))?
+                    .invoke(env, vec![a.clone(), b.clone()])?;
+                Ok(eq.downcast::<Bit>()?.val())
+            }
+            _ => Ok(true),
+        }
+    }
+}
+
+unsafe impl<'ip> ValItem<'ip> for Union<'ip> {
+    fn allow_cast(ty: Type) -> Result<()> {
+        if let Type::Union(_, _) = ty {
+            Ok(())
+        } else {
+            Err(ExceptionKind::InvalidType(Type::named("union"), ty).into())
+        }
+    }
+
+    fn clone(&self) -> Box<dyn ValItem<'ip> + 'ip> {
+        Box::new(Union {
+            ty: Clone::clone(&self.ty),
+            variant: self.variant,
+            payload: self.payload.clone(),
+        })
+    }
+
+    fn ty(&self) -> Type {
+        Clone::clone(&self.ty)
+    }
+
+    fn write(&self, w: &mut dyn Write) -> io::Result<()> {
+        write!(w, "{}", Union::variant_name(&self.ty, self.variant))?;
+        if let Some(payload) = &self.payload {
+            write!(w, "(")?;
+            payload.data.write(w)?;
+            write!(w, ")")?;
+        }
+        Ok(())
+    }
+
+    fn get_field(&self, _name: &str) -> Option<Value<'ip>> {
+        None
+    }
+
+    fn get_op(&self, op: Op) -> Option<Fn<'ip>> {
+        let ty = ValItem::ty(self);
+        match op {
+            Op::Eq => Some(BuiltinFn::new(
+                "union_eq",
+                Type::named("bit"),
+                vec![Clone::clone(&ty), ty],
+                |env, args| {
+                    let a = args[0].downcast::<Union<'_>>()?;
+                    let b = args[1].downcast::<Union<'_>>()?;
+
+                    Ok(Value::new(Bit::new(a.equals(env, b)?)))
+                }
+            ).into()),
+            Op::Neq => Some(BuiltinFn::new(
+                "union_neq",
+                Type::named("bit"),
+                vec![Clone::clone(&ty), ty],
+                |env, args| {
+                    let a = args[0].downcast::<Union<'_>>()?;
+                    let b = args[1].downcast::<Union<'_>>()?;
+
+                    Ok(Value::new(Bit::new(!a.equals(env, b)?)))
+                }
+            ).into()),
+            _ => None,
+        }
+    }
+}
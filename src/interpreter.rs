@@ -0,0 +1,399 @@
+use core::fmt;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+use std::{fs, io, iter};
+
+use chumsky::error::SimpleReason;
+
+use crate::ast::{Exports, File, Import, ImportBinding, Span};
+use crate::diagnostic::{Diagnostic, Location, SourceId, Sources};
+use crate::interp::{Array, BuiltinFn, CharArray, Env, Exception, ExceptionKind, Module, Value};
+use crate::parser::parse;
+use crate::token::tokenize;
+use crate::vm;
+
+/// A parse failure, detached from the tokens of the source it came from
+#[derive(Clone, Debug)]
+pub struct ParseError {
+    source: SourceId,
+    span: Span,
+    message: String,
+}
+
+impl ParseError {
+    pub fn span(&self) -> Span {
+        self.span.clone()
+    }
+
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+
+    pub fn diagnostic(&self) -> Diagnostic {
+        Diagnostic::new("Parse Failure", self.message.clone())
+            .with_location(Some(Location::new(self.source, self.span.clone())))
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+#[derive(Debug)]
+pub enum Error {
+    Io(io::Error),
+    Parse(Vec<ParseError>),
+    /// Problems found by [`Interpreter::check`]
+    Check(Vec<Diagnostic>),
+    Exception(Exception),
+    /// The program called `exit` with this status
+    Exit(u8),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Io(e) => write!(f, "Couldn't read provided file: {}", e),
+            Error::Parse(errs) => {
+                for (idx, err) in errs.iter().enumerate() {
+                    if idx != 0 {
+                        writeln!(f)?;
+                    }
+                    write!(f, "Parse Failure: {}", err)?;
+                }
+                Ok(())
+            }
+            Error::Check(diags) => {
+                for (idx, diag) in diags.iter().enumerate() {
+                    if idx != 0 {
+                        writeln!(f)?;
+                    }
+                    write!(f, "{}", diag)?;
+                }
+                Ok(())
+            }
+            Error::Exception(e) => write!(f, "Exception: {}", e),
+            Error::Exit(status) => write!(f, "Exited with status {}", status),
+        }
+    }
+}
+
+impl Error {
+    /// Every diagnostic this error is made up of, in the order they should be shown
+    pub fn diagnostics(&self) -> Vec<Diagnostic> {
+        match self {
+            Error::Io(e) => vec![Diagnostic::new("Couldn't read provided file", e.to_string())],
+            Error::Parse(errs) => errs.iter().map(ParseError::diagnostic).collect(),
+            Error::Check(diags) => diags.clone(),
+            Error::Exception(e) => vec![e.diagnostic()],
+            // Exiting is how the program asked to stop, so there's nothing to report
+            Error::Exit(_) => Vec::new(),
+        }
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(e: io::Error) -> Self {
+        Error::Io(e)
+    }
+}
+
+impl From<Exception> for Error {
+    fn from(e: Exception) -> Self {
+        match e.kind() {
+            ExceptionKind::Exit(status) => Error::Exit(*status),
+            _ => Error::Exception(e),
+        }
+    }
+}
+
+/// The embedding entry point for goose. Holds a single persistent environment, so definitions
+/// made by one run are visible to the next.
+#[derive(Debug, Default)]
+pub struct Interpreter {
+    env: Env<'static>,
+    sources: Sources,
+    /// Directories to look for imports in, after the directory of the importing file
+    search_path: Vec<PathBuf>,
+    /// Every module loaded so far, by canonical path, so each file only runs once
+    modules: HashMap<PathBuf, Value<'static>>,
+    /// The modules being loaded right now, outermost first, along with the import that asked
+    /// for each
+    loading: Vec<(PathBuf, Location)>,
+}
+
+impl Interpreter {
+    pub fn new() -> Interpreter {
+        Interpreter {
+            env: Env::new(),
+            sources: Sources::default(),
+            search_path: Vec::new(),
+            modules: HashMap::new(),
+            loading: Vec::new(),
+        }
+    }
+
+    pub fn env(&mut self) -> &mut Env<'static> {
+        &mut self.env
+    }
+
+    /// Register a host function as a global, under its own name
+    pub fn register_fn(&mut self, f: BuiltinFn) {
+        let name = f.name().to_string();
+        self.env.insert_global(&name, Value::new(crate::interp::Fn::from(f)));
+    }
+
+    pub fn set_global(&mut self, name: &str, value: Value<'static>) {
+        self.env.insert_global(name, value);
+    }
+
+    /// Give the program its command line arguments, as the `args` global
+    pub fn set_args<S: AsRef<str>>(&mut self, args: &[S]) {
+        let args = args
+            .iter()
+            .map(|arg| Value::new(CharArray::new(arg.as_ref().to_string())))
+            .collect();
+        self.env.insert_global("args", Value::new(Array(args)));
+    }
+
+    pub fn global(&self, name: &str) -> Option<Value<'static>> {
+        self.env.lookup_var(name)
+    }
+
+    /// Every source this interpreter has parsed, which diagnostics point into
+    pub fn sources(&self) -> &Sources {
+        &self.sources
+    }
+
+    /// Register source text under a name, such as its path, for use in diagnostics
+    pub fn add_source(&mut self, name: &str, text: &str) -> SourceId {
+        self.sources.add(name, text)
+    }
+
+    /// Parse a source previously registered with [`Interpreter::add_source`]
+    pub fn parse(&self, source: SourceId) -> Result<File, Error> {
+        let tokens = tokenize(self.sources.text(source));
+        let mut file = parse(&tokens).map_err(|errs| {
+            Error::Parse(
+                errs.into_iter()
+                    .map(|err| ParseError {
+                        source,
+                        span: err.span(),
+                        // chumsky's `Display` ignores custom messages
+                        message: match err.reason() {
+                            SimpleReason::Custom(message) => message.clone(),
+                            _ => err.to_string(),
+                        },
+                    })
+                    .collect(),
+            )
+        })?;
+        file.set_source(source);
+        Ok(file)
+    }
+
+    /// Check the types in a file without running it, against the current environment. The files it
+    /// imports are checked along with it, but none of them run.
+    pub fn check(&mut self, file: &File) -> Result<(), Error> {
+        let mut diags = Vec::new();
+        let mut checked = HashMap::new();
+        self.as_root(file, |interp| interp.check_file(file, &mut checked, &mut diags))?;
+        if diags.is_empty() {
+            Ok(())
+        } else {
+            Err(Error::Check(diags))
+        }
+    }
+
+    /// Check a file and everything it imports, giving what the file defines. Each file is checked
+    /// once, however many times it's imported.
+    fn check_file(
+        &mut self,
+        file: &File,
+        checked: &mut HashMap<PathBuf, Rc<Exports>>,
+        diags: &mut Vec<Diagnostic>,
+    ) -> Result<Exports, Error> {
+        let source = file.source();
+        let dir = self.dir_of(source);
+
+        let mut imports = Vec::new();
+        for import in file.imports() {
+            let site = Location::new(source, import.span.clone());
+            let path = match self.find_module(&dir, import, site.clone()) {
+                Ok(path) => path,
+                Err(e) => {
+                    diags.push(e.diagnostic());
+                    imports.push(None);
+                    continue;
+                }
+            };
+            if let Some(exports) = checked.get(&path) {
+                imports.push(Some(Rc::clone(exports)));
+                continue;
+            }
+
+            let text = fs::read_to_string(&path)?;
+            let id = self.add_source(&path.display().to_string(), &text);
+            let module = self.parse(id)?;
+            self.loading.push((path.clone(), site));
+            let exports = self.check_file(&module, checked, diags);
+            self.loading.pop();
+            let exports = Rc::new(exports?);
+            checked.insert(path, Rc::clone(&exports));
+            imports.push(Some(exports));
+        }
+
+        let (found, exports) = file.check(&self.env, &imports);
+        diags.extend(found);
+        Ok(exports)
+    }
+
+    /// Render an error with the source it points into
+    pub fn report<W: io::Write>(&self, err: &Error, color: bool, mut w: W) -> io::Result<()> {
+        for diag in err.diagnostics() {
+            diag.write(&self.sources, color, &mut w)?;
+        }
+        Ok(())
+    }
+
+    /// Look for imports in `dir` when they aren't next to the file importing them. Directories
+    /// are searched in the order they were added.
+    pub fn add_search_path<P: Into<PathBuf>>(&mut self, dir: P) {
+        self.search_path.push(dir.into());
+    }
+
+    /// Find the file an import refers to, first next to the importing file, then on the search
+    /// path. A path without an extension is taken to be a `.hnk` file.
+    fn resolve(&self, dir: &Path, path: &str) -> Option<PathBuf> {
+        let mut path = PathBuf::from(path);
+        if path.extension().is_none() {
+            path.set_extension("hnk");
+        }
+        iter::once(dir)
+            .chain(self.search_path.iter().map(PathBuf::as_path))
+            .map(|dir| dir.join(&path))
+            .find(|candidate| candidate.is_file())
+            .and_then(|found| found.canonicalize().ok())
+    }
+
+    /// Load everything a file imports, and bind it in the current scope
+    fn import_all(&mut self, file: &File) -> Result<(), Error> {
+        let source = file.source();
+        let dir = self.dir_of(source);
+
+        for import in file.imports() {
+            let site = Location::new(source, import.span.clone());
+            let module = self.load(&dir, import, site)?;
+            match import.binding() {
+                ImportBinding::Namespace(name) => self.env.insert_var(name, module),
+                ImportBinding::Select(names) => {
+                    let module = module.downcast::<Module<'_>>()?;
+                    for (name, def) in names {
+                        let val = module.get(def).cloned().ok_or_else(|| {
+                            let kind = ExceptionKind::NotExported(import.path().node.clone(), def.node.clone());
+                            Exception::from(kind).at(Location::new(source, def.span.clone()))
+                        })?;
+                        self.env.insert_var(name, val);
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// The directory the file a source was read from is in, which its imports are relative to
+    fn dir_of(&self, source: SourceId) -> PathBuf {
+        Path::new(self.sources.name(source)).parent().unwrap_or(Path::new("")).to_path_buf()
+    }
+
+    /// Find the file `import` refers to, making sure it isn't already being loaded
+    fn find_module(&self, dir: &Path, import: &Import, site: Location) -> Result<PathBuf, Exception> {
+        let written = &import.path().node;
+        let path = self.resolve(dir, written).ok_or_else(|| {
+            let kind = ExceptionKind::ImportNotFound(written.clone());
+            Exception::from(kind).at(Location::new(site.source, import.path().span.clone()))
+        })?;
+
+        // Point out every import in the cycle, starting from the one that closes it
+        if let Some(idx) = self.loading.iter().position(|(loading, _)| *loading == path) {
+            let cycle = self.loading[idx + 1..].iter().fold(
+                Exception::from(ExceptionKind::ImportCycle(written.clone())).at(site),
+                |e, (imported, site)| {
+                    let name = imported.file_name().unwrap_or(imported.as_os_str());
+                    e.with_label(site.clone(), format!("`{}` is imported here", name.to_string_lossy()))
+                },
+            );
+            return Err(cycle);
+        }
+        Ok(path)
+    }
+
+    /// Load the module `import` refers to, running it if it hasn't been already
+    fn load(&mut self, dir: &Path, import: &Import, site: Location) -> Result<Value<'static>, Error> {
+        let path = self.find_module(dir, import, site.clone())?;
+        if let Some(module) = self.modules.get(&path) {
+            return Ok(module.clone());
+        }
+
+        let text = fs::read_to_string(&path)?;
+        let name = path.display().to_string();
+        let source = self.add_source(&name, &text);
+        let file = self.parse(source)?;
+
+        // A module runs in a scope of its own, and everything left in it becomes the module
+        self.loading.push((path.clone(), site));
+        let caller = self.env.enter_module();
+        let ran = self.import_all(&file).and_then(|()| {
+            vm::run(&mut self.env, &file.compile()).map_err(Error::from)
+        });
+        let defs = self.env.scope().vars();
+        self.env.restore_scope(caller);
+        self.loading.pop();
+        ran?;
+
+        let module = Value::new(Module::new(&name, defs));
+        self.modules.insert(path, module.clone());
+        Ok(module)
+    }
+
+    /// Count a file being run or checked as loading while `f` deals with its imports, so a file
+    /// that ends up importing itself is reported as a cycle
+    fn as_root<T>(&mut self, file: &File, f: impl FnOnce(&mut Self) -> T) -> T {
+        let depth = self.loading.len();
+        if let Ok(path) = Path::new(self.sources.name(file.source())).canonicalize() {
+            self.loading.push((path, Location::new(file.source(), 0..0)));
+        }
+        let out = f(self);
+        self.loading.truncate(depth);
+        out
+    }
+
+    /// Run an already parsed file, returning the value of its last statement. Its imports are
+    /// loaded first.
+    pub fn run(&mut self, file: &File) -> Result<Value<'static>, Error> {
+        self.as_root(file, |interp| interp.import_all(file))?;
+        let chunk = file.compile();
+        let depth = self.env.scope_depth();
+        vm::run(&mut self.env, &chunk).map_err(|e| {
+            self.env.truncate_scopes(depth);
+            Error::from(e)
+        })
+    }
+
+    pub fn run_str(&mut self, source: &str) -> Result<Value<'static>, Error> {
+        let name = format!("<input:{}>", self.sources.len());
+        let id = self.add_source(&name, source);
+        let file = self.parse(id)?;
+        self.run(&file)
+    }
+
+    pub fn run_file<P: AsRef<Path>>(&mut self, path: P) -> Result<Value<'static>, Error> {
+        let source = std::fs::read_to_string(&path)?;
+        let id = self.add_source(&path.as_ref().display().to_string(), &source);
+        let file = self.parse(id)?;
+        self.run(&file)
+    }
+}
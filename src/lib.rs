@@ -0,0 +1,25 @@
+#![warn(
+    elided_lifetimes_in_paths,
+    explicit_outlives_requirements,
+    missing_abi,
+    noop_method_call,
+    semicolon_in_expressions_from_macros,
+    unused_import_braces,
+    unused_lifetimes,
+    clippy::missing_panics_doc,
+    clippy::doc_markdown,
+    clippy::ptr_as_ptr,
+    clippy::cloned_instead_of_copied,
+    clippy::unreadable_literal
+)]
+
+pub mod ast;
+pub mod diagnostic;
+pub mod interp;
+pub mod parser;
+pub mod token;
+pub mod vm;
+
+mod interpreter;
+
+pub use interpreter::{Error, Interpreter, ParseError};
@@ -1,12 +1,8 @@
-#![feature(iter_intersperse)]
-#![feature(io_safety)]
-#![feature(once_cell)]
 #![warn(
     elided_lifetimes_in_paths,
     explicit_outlives_requirements,
     missing_abi,
     noop_method_call,
-    pointer_structural_match,
     semicolon_in_expressions_from_macros,
     unused_import_braces,
     unused_lifetimes,
@@ -17,68 +13,137 @@
     clippy::unreadable_literal
 )]
 
-extern crate core;
-
+use std::io::{self, IsTerminal};
+use std::path::{Path, PathBuf};
 use std::process::ExitCode;
 
-mod ast;
+use cmd::Emit;
+use goose::ast::File;
+use goose::interp::Honk;
+use goose::token::tokenize;
+use goose::{Error, Interpreter};
+
 mod cmd;
-mod interp;
-mod parser;
-mod token;
+mod repl;
+
+/// Render an error to stdout, in color if it's a terminal
+fn report(interp: &Interpreter, err: &Error) {
+    let stdout = io::stdout();
+    let color = stdout.is_terminal();
+    if interp.report(err, color, stdout.lock()).is_err() {
+        println!("{}", err);
+    }
+}
+
+/// Point `honk` output at the file given on the command line, or the default one, truncating it
+/// before anything runs
+fn open_honk(interp: &mut Interpreter, args: &cmd::Command) -> bool {
+    let path = args.honk().map_or(Path::new(Honk::DEFAULT_PATH), PathBuf::as_path);
+    match interp.env().honk().open(path) {
+        Ok(()) => true,
+        Err(e) => {
+            println!("Couldn't open {} for honk output: {}", path.display(), e);
+            false
+        }
+    }
+}
+
+/// Write out anything still buffered for `honk`, as the program exits
+fn flush_honk(interp: &mut Interpreter) -> bool {
+    match interp.env().honk().flush() {
+        Ok(()) => true,
+        Err(e) => {
+            println!("Couldn't write honk output: {}", e);
+            false
+        }
+    }
+}
+
+fn emit_source(emit: &[Emit], source: &str) {
+    if emit.contains(&Emit::Tokens) {
+        println!(
+            "{:?}",
+            tokenize(source).iter().map(|(tok, _)| tok).collect::<Vec<_>>()
+        );
+    }
+}
+
+fn emit_file(emit: &[Emit], ast: &File) {
+    if emit.contains(&Emit::Ast) {
+        println!("{:#?}", ast);
+    }
+
+    if emit.contains(&Emit::Bytecode) {
+        println!("{}", ast.compile());
+    }
+}
 
 fn main() -> ExitCode {
     use clap::Parser;
-    use cmd::{Command, Emit};
-    use interp::Env;
-    use parser::parse;
-    use token::tokenize;
+    use cmd::{Action, Command};
 
     let args = Command::parse();
 
-    let file = &args.file;
+    let (path, check) = match (&args.action, &args.file) {
+        (Some(Action::Repl), _) | (None, None) => return repl::run(&args),
+        (Some(Action::Check { file }), _) => (file, true),
+        (None, Some(path)) => (path, false),
+    };
+
+    let mut interp = Interpreter::new();
+    interp.env().set_strict_nan(args.strict_nan());
+    interp.set_args(args.script_args());
+    for dir in args.search_path() {
+        interp.add_search_path(dir);
+    }
 
-    let file = match std::fs::read_to_string(file) {
+    let file = match std::fs::read_to_string(path) {
         Ok(file) => file,
         Err(e) => {
-            println!("Couldn't read provided file: {}", e);
+            report(&interp, &Error::Io(e));
             return ExitCode::FAILURE;
         }
     };
 
-    let tokens = tokenize(&file);
+    emit_source(args.emit(), &file);
 
-    if args.should_emit(Emit::Tokens) {
-        println!(
-            "{:?}",
-            tokens.iter().map(|(tok, _)| tok).collect::<Vec<_>>()
-        );
-    }
-
-    let ast = match parse(&tokens) {
+    let source = interp.add_source(&path.display().to_string(), &file);
+    let ast = match interp.parse(source) {
         Ok(ast) => ast,
-        Err(errs) => {
-            for err in errs {
-                println!("Parse Failure: {}", err);
-                println!(
-                    "at {}",
-                    &file[err.span().start.saturating_sub(10)
-                        ..usize::min(err.span().end + 10, file.len())]
-                )
-            }
+        Err(e) => {
+            report(&interp, &e);
             return ExitCode::FAILURE;
         }
     };
 
-    if args.should_emit(Emit::Ast) {
-        println!("{:#?}", ast);
+    emit_file(args.emit(), &ast);
+
+    if check {
+        return match interp.check(&ast) {
+            Ok(()) => ExitCode::SUCCESS,
+            Err(e) => {
+                report(&interp, &e);
+                ExitCode::FAILURE
+            }
+        };
     }
 
-    let mut ctx = Env::default();
-    if let Err(e) = ast.interpret(&mut ctx) {
-        println!("Exception: {}", e);
-        ExitCode::FAILURE
+    if !open_honk(&mut interp, &args) {
+        return ExitCode::FAILURE;
+    }
+
+    let status = match interp.run(&ast) {
+        Ok(_) => ExitCode::SUCCESS,
+        Err(Error::Exit(status)) => ExitCode::from(status),
+        Err(e) => {
+            report(&interp, &e);
+            ExitCode::FAILURE
+        }
+    };
+
+    if flush_honk(&mut interp) {
+        status
     } else {
-        ExitCode::SUCCESS
+        ExitCode::FAILURE
     }
 }
@@ -0,0 +1,163 @@
+use std::io;
+use std::io::Write;
+use std::mem;
+use std::process::ExitCode;
+
+use clap::ArgEnum;
+use rustyline::error::ReadlineError;
+use rustyline::Editor;
+
+use goose::ast::{Stmt, Type};
+use goose::parser::parse;
+use goose::token::{in_open_comment, tokenize, Token};
+use goose::{Error, Interpreter};
+
+use crate::cmd::{Command, Emit};
+use crate::{emit_file, emit_source, flush_honk, open_honk, report};
+
+const HELP: &str = "\
+:tokens, :ast, :bytecode  Toggle printing that stage of each input
+:help                     Show this message
+:quit                     Leave the session";
+
+/// Whether more lines are needed before the input can be parsed, due to an open bracket or
+/// multi-line comment
+fn is_incomplete(source: &str) -> bool {
+    if in_open_comment(source) {
+        return true;
+    }
+
+    let depth = tokenize(source)
+        .iter()
+        .fold(0isize, |depth, (tok, _)| match tok {
+            Token::OpenBracket | Token::OpenCurly | Token::OpenParen => depth + 1,
+            Token::CloseBracket | Token::CloseCurly | Token::CloseParen => depth - 1,
+            _ => depth,
+        });
+
+    depth > 0
+}
+
+/// Handle a meta-command, returning whether the session should end
+fn meta(line: &str, emit: &mut Vec<Emit>) -> bool {
+    match line {
+        ":quit" | ":q" => return true,
+        ":help" => println!("{}", HELP),
+        _ => match Emit::from_str(&line[1..], true) {
+            Ok(e) => {
+                if let Some(idx) = emit.iter().position(|&other| other == e) {
+                    emit.remove(idx);
+                    println!("Stopped emitting {}", &line[1..]);
+                } else {
+                    emit.push(e);
+                    println!("Emitting {}", &line[1..]);
+                }
+            }
+            Err(_) => println!("Unknown command `{}`, try :help", line),
+        },
+    }
+    false
+}
+
+/// Run one input, returning the status to exit with if it called `exit`
+fn eval(interp: &mut Interpreter, emit: &[Emit], source: &str) -> Option<u8> {
+    emit_source(emit, source);
+
+    // Allow leaving the semicolon off the last statement
+    let terminated = format!("{};", source.trim_end());
+    let text = if parse(&tokenize(source)).is_err() && parse(&tokenize(&terminated)).is_ok() {
+        &terminated
+    } else {
+        source
+    };
+
+    let name = format!("<repl:{}>", interp.sources().len());
+    let id = interp.add_source(&name, text);
+    let ast = match interp.parse(id) {
+        Ok(ast) => ast,
+        Err(e) => {
+            report(interp, &e);
+            return None;
+        }
+    };
+
+    emit_file(emit, &ast);
+
+    match interp.run(&ast) {
+        Ok(val) => {
+            let is_expr = matches!(ast.stmts().last().map(|s| &s.node), Some(Stmt::Expr(_)));
+            if is_expr && val.ty() != Type::named("null") {
+                let mut out = io::stdout();
+                if let Err(e) = val.write(&mut out) {
+                    report(interp, &Error::Exception(e));
+                }
+                println!();
+                let _ = out.flush();
+            }
+        }
+        Err(Error::Exit(status)) => return Some(status),
+        Err(e) => report(interp, &e),
+    }
+    None
+}
+
+pub fn run(args: &Command) -> ExitCode {
+    let mut editor = Editor::<()>::new();
+    let mut interp = Interpreter::new();
+    interp.env().set_strict_nan(args.strict_nan());
+    if !open_honk(&mut interp, args) {
+        return ExitCode::FAILURE;
+    }
+    interp.set_args(args.script_args());
+    for dir in args.search_path() {
+        interp.add_search_path(dir);
+    }
+    let mut emit = args.emit().to_vec();
+    let mut buffer = String::new();
+
+    loop {
+        let prompt = if buffer.is_empty() { "goose> " } else { "...... " };
+
+        match editor.readline(prompt) {
+            Ok(line) => {
+                if buffer.is_empty() && line.trim_start().starts_with(':') {
+                    editor.add_history_entry(line.trim());
+                    if meta(line.trim(), &mut emit) {
+                        break;
+                    }
+                    continue;
+                }
+
+                buffer.push_str(&line);
+                buffer.push('\n');
+                if is_incomplete(&buffer) {
+                    continue;
+                }
+
+                let source = mem::take(&mut buffer);
+                if source.trim().is_empty() {
+                    continue;
+                }
+                editor.add_history_entry(source.trim_end());
+                if let Some(status) = eval(&mut interp, &emit, &source) {
+                    flush_honk(&mut interp);
+                    return ExitCode::from(status);
+                }
+            }
+            // Ctrl-C abandons the current input, rather than the session
+            Err(ReadlineError::Interrupted) => buffer.clear(),
+            Err(ReadlineError::Eof) => break,
+            Err(e) => {
+                println!("{}", e);
+                flush_honk(&mut interp);
+                return ExitCode::FAILURE;
+            }
+        }
+    }
+
+    if flush_honk(&mut interp) {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::FAILURE
+    }
+}
@@ -22,15 +22,31 @@ pub enum Token<'a> {
 
     #[regex(r"[a-zA-Z_][a-zA-Z0-9_]*")]
     Ident(&'a str),
-    #[regex(r"\d+")]
+    // Digits can be separated by underscores, and the parser checks each one sits between two
+    // digits and that the value fits
+    #[regex(r"\d[\d_]*")]
+    #[regex(r"0x[0-9a-fA-F_]+")]
+    #[regex(r"0o[0-7_]+")]
+    #[regex(r"0b[01_]+")]
     Int(&'a str),
-    #[regex(r"\d+\.\d*")]
+    #[regex(r"\d[\d_]*n")]
+    #[regex(r"0x[0-9a-fA-F_]+n")]
+    #[regex(r"0o[0-7_]+n")]
+    #[regex(r"0b[01_]+n")]
+    BigInt(&'a str),
+    #[regex(r"\d[\d_]*\.[\d_]*([eE][+-]?\d[\d_]*)?")]
+    #[regex(r"\.\d[\d_]*([eE][+-]?\d[\d_]*)?")]
+    #[regex(r"\d[\d_]*[eE][+-]?\d[\d_]*")]
     Float(&'a str),
+    // `0b` on its own is a bit, and only a prefix when binary digits follow it, which makes a
+    // longer match
     #[regex(r"(0|1)b")]
     Bit(&'a str),
-    #[regex(r"'.'")]
+    // Escapes are decoded by the parser, which also checks a char literal holds one char, so
+    // mistakes in either can be reported with a span
+    #[regex(r"'([^'\\]|\\.)*'")]
     Char(&'a str),
-    #[regex(r#""([^"]|\\")*""#)]
+    #[regex(r#""([^"\\]|\\.)*""#)]
     Str(&'a str),
 
     #[token("`")]
@@ -96,6 +112,14 @@ pub enum Token<'a> {
     EqEq,
     #[token("!=")]
     BangEq,
+    #[token("<=")]
+    LtEq,
+    #[token(">=")]
+    GtEq,
+    #[token("&&")]
+    AndAnd,
+    #[token("||")]
+    PipePipe,
     #[token("+=")]
     PlusEq,
     #[token("-=")]
@@ -135,6 +159,8 @@ impl fmt::Display for Token<'_> {
             Token::Ident(i) => *i,
             Token::Int("...") => "<int>",
             Token::Int(i) => *i,
+            Token::BigInt("...") => "<bigint>",
+            Token::BigInt(i) => *i,
             Token::Float("...") => "<float>",
             Token::Float(f) => *f,
             Token::Bit("...") => "<bit>",
@@ -176,6 +202,10 @@ impl fmt::Display for Token<'_> {
             Token::Arrow => "->",
             Token::EqEq => "==",
             Token::BangEq => "!=",
+            Token::LtEq => "<=",
+            Token::GtEq => ">=",
+            Token::AndAnd => "&&",
+            Token::PipePipe => "||",
             Token::PlusEq => "+=",
             Token::DashEq => "-=",
             Token::StarEq => "*=",
@@ -190,10 +220,32 @@ impl fmt::Display for Token<'_> {
 }
 
 pub fn tokenize(file: &str) -> Vec<(Token<'_>, Range<usize>)> {
+    let mut tokens = Vec::new();
+    for (token, span) in Token::lexer(file).spanned() {
+        match token {
+            Token::Comment(_) => (),
+            // `10.` is a float, but in `a#0.len` the dot starts a field, so split it back off
+            Token::Float(f) if f.ends_with('.') && file[span.end..].starts_with(is_ident_start) => {
+                let dot = span.end - 1;
+                tokens.push((Token::Int(&f[..f.len() - 1]), span.start..dot));
+                tokens.push((Token::Dot, dot..span.end));
+            }
+            token => tokens.push((token, span)),
+        }
+    }
+    tokens
+}
+
+/// Whether `file` ends inside a `$$$` comment that hasn't been closed. Without its closing `$$$`
+/// the comment is lexed as an error running to the end of the input.
+pub fn in_open_comment(file: &str) -> bool {
     Token::lexer(file)
         .spanned()
-        .filter(|(t, _)| !matches!(t, Token::Comment(_)))
-        .collect()
+        .any(|(token, span)| token == Token::Error && file[span].starts_with("$$$"))
+}
+
+fn is_ident_start(c: char) -> bool {
+    c.is_ascii_alphabetic() || c == '_'
 }
 
 /*pub fn tokenize_with_comments(file: &str) -> Vec<(Token<'_>, Range<usize>)> {
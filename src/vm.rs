@@ -0,0 +1,292 @@
+use crate::diagnostic::Location;
+use crate::interp::{
+    Array, Bit, Env, Exception, ExceptionKind, Fn, Int, Record, Result, Scope, Type, Value,
+};
+
+mod chunk;
+
+pub use chunk::{Chunk, Const, FnProto, Instr};
+
+/// The function whose body is running, along with the type this call must return
+#[derive(Copy, Clone)]
+struct Callee<'a> {
+    proto: &'a FnProto,
+    ret: &'a Type,
+}
+
+/// Run a top-level chunk, returning the value it leaves on the stack
+pub fn run<'ip>(env: &mut Env<'ip>, chunk: &Chunk) -> Result<Value<'ip>> {
+    exec(env, chunk, None)
+}
+
+/// Call a compiled function with the given arguments, in a new frame and a new scope nested in
+/// the one the function captured
+pub fn call<'ip>(
+    env: &mut Env<'ip>,
+    proto: &FnProto,
+    scope: &Scope<'ip>,
+    args: Vec<Value<'ip>>,
+) -> Result<Value<'ip>> {
+    if proto.args.len() != args.len() {
+        let kind = ExceptionKind::InvalidArgCount(proto.name.clone(), proto.args.len(), args.len());
+        return Err(kind.into());
+    }
+
+    let caller = env.enter_scope(scope);
+    env.push_frame(proto.name());
+
+    let out = bind_args(env, proto, args)
+        .and_then(|ret| exec(env, &proto.body, Some(Callee { proto, ret: &ret })))
+        .map_err(|e| match env.frames().last() {
+            Some(frame) => e.in_frame(frame),
+            None => e,
+        });
+
+    env.pop_frame();
+    env.restore_scope(caller);
+
+    out
+}
+
+/// Define the arguments of a call, binding the function's type parameters to their types. The
+/// parameters are defined as aliases too, so closures in the body see them. Returns the type the
+/// call must return, which is unchecked where it uses parameters left unbound.
+fn bind_args<'ip>(env: &mut Env<'ip>, proto: &FnProto, args: Vec<Value<'ip>>) -> Result<Type> {
+    let mut generics = proto.generics.iter().map(|param| (param.clone(), None)).collect::<Vec<_>>();
+    for ((name, ty), val) in proto.args.iter().zip(args) {
+        let location = Location::new(proto.source(), ty.span.clone());
+        ty.validate_generic(env, &val, &mut generics).map_err(|e| {
+            e.with_label(location.clone(), format!("parameter `{}` declared here", &**name))
+        })?;
+        env.insert_var_at(name, val, location);
+    }
+
+    for (param, bound) in &mut generics {
+        let bound = bound.get_or_insert_with(|| Type::named("any"));
+        env.insert_var(param, Value::new(bound.clone()));
+    }
+    Ok(proto.ret.node.substitute(&generics))
+}
+
+/// Evaluate a limit. Limits which fail, or don't produce a bit, are treated as not reached, except
+/// that `exit` still unwinds the program.
+fn limit_reached(env: &mut Env<'_>, limit: &Chunk) -> Result<bool> {
+    match exec(env, limit, None) {
+        Ok(val) => Ok(val.downcast::<Bit>().is_ok_and(Bit::val)),
+        Err(e) if matches!(e.kind(), ExceptionKind::Exit(_)) => Err(e),
+        Err(_) => Ok(false),
+    }
+}
+
+/// Run a chunk until it returns. If it's the body of a function, its limit is checked at each
+/// `CheckLimit`.
+fn exec<'ip>(env: &mut Env<'ip>, chunk: &Chunk, func: Option<Callee<'_>>) -> Result<Value<'ip>> {
+    let mut stack: Vec<Value<'ip>> = Vec::new();
+    let mut pc = 0;
+
+    loop {
+        let instr = chunk.code[pc];
+        pc += 1;
+
+        match step(env, chunk, func, &mut stack, &mut pc, instr) {
+            Ok(Some(val)) => return Ok(val),
+            Ok(None) => (),
+            Err(e) => {
+                let location = chunk.location(pc - 1);
+                let e = match instr {
+                    Instr::Call(_) => e.called_from(location.clone()),
+                    _ => e,
+                };
+                return Err(e.at(location));
+            }
+        }
+    }
+}
+
+/// Execute a single instruction, returning a value if the chunk should return
+fn step<'ip>(
+    env: &mut Env<'ip>,
+    chunk: &Chunk,
+    func: Option<Callee<'_>>,
+    stack: &mut Vec<Value<'ip>>,
+    pc: &mut usize,
+    instr: Instr,
+) -> Result<Option<Value<'ip>>> {
+    let location = chunk.location(*pc - 1);
+
+    match instr {
+        Instr::Const(c) => stack.push(chunk.constant(c).to_value()),
+        Instr::Null => stack.push(Value::null()),
+        Instr::Load(n) => {
+            let name = chunk.name(n);
+            let val = env
+                .lookup_var(name)
+                .ok_or_else(|| ExceptionKind::NameNotFound(name.clone()))?;
+            stack.push(val);
+        }
+        Instr::Define(n) => {
+            let val = top(stack).clone();
+            env.insert_var_at(chunk.name(n), val, location);
+        }
+        Instr::DefineFirst(n) => {
+            if env.is_first_iter() {
+                let val = top(stack).clone();
+                env.insert_var_at(chunk.name(n), val, location);
+            } else {
+                pop(stack);
+                stack.push(Value::null());
+            }
+        }
+        Instr::Assign(n) => {
+            let name = chunk.name(n);
+            let val = top(stack).clone();
+            let ty = env
+                .lookup_var_ty(name)
+                .ok_or_else(|| ExceptionKind::NameNotFound(name.clone()))?;
+            if !ty.agrees(&val.ty()) {
+                let mut e = Exception::from(ExceptionKind::InvalidType(ty.clone(), val.ty()));
+                if let Some(defined) = env.lookup_location(name) {
+                    e = e.with_label(
+                        defined,
+                        format!("variable first defined with type {} here", ty.pretty()),
+                    );
+                }
+                return Err(e);
+            }
+            env.set_var(name, val);
+        }
+        Instr::DefineType(n, t) => {
+            let ty = chunk.ty(t);
+            // A union's variants are constructed by functions named after them
+            if let Type::Union(_, variants) = ty {
+                for (idx, (name, _)) in variants.iter().enumerate() {
+                    let constructor = Fn::Variant(ty.clone(), idx);
+                    env.insert_var_at(name, Value::new(constructor), location.clone());
+                }
+            }
+            env.insert_var_at(chunk.name(n), Value::new(ty.clone()), location);
+        }
+        Instr::MakeFn(p) => {
+            let f = Fn::closure(chunk.proto(p).clone(), env.scope().clone());
+            stack.push(Value::new(f));
+        }
+        Instr::MakeArray(len) => {
+            let vals = stack.split_off(stack.len() - len as usize);
+
+            // Every element has to agree with the most specific type seen so far
+            let mut tys = vals.iter().map(|val| val.ty());
+            if let Some(mut joined) = tys.next() {
+                for ty in tys {
+                    joined = match joined.join(&ty) {
+                        Some(joined) => joined,
+                        None => return Err(ExceptionKind::InvalidType(joined, ty).into()),
+                    };
+                }
+            }
+
+            stack.push(Value::new(Array(vals)));
+        }
+        Instr::MakeRecord(n, l) => {
+            let fields = chunk.field_list(l);
+            let vals = stack.split_off(stack.len() - fields.len());
+            let name = chunk.name(n);
+            let ty = env
+                .lookup_var(name)
+                .ok_or_else(|| ExceptionKind::NameNotFound(name.clone()))?
+                .downcast::<Type>()?
+                .resolve(env);
+            let record = Record::new(env, ty, fields.iter().cloned().zip(vals).collect())?;
+            stack.push(Value::new(record));
+        }
+        Instr::UnOp(op) => {
+            let rval = pop(stack);
+            let out = rval
+                .get_op(op)
+                .filter(|f| operands_agree(&f.arg_tys(), &[rval.ty()]))
+                .ok_or_else(|| ExceptionKind::InvalidOp(op, rval.ty(), None))?
+                .invoke(env, vec![rval])?;
+            stack.push(out);
+        }
+        Instr::BinOp(op) => {
+            let rval = pop(stack);
+            let lval = pop(stack);
+            // Operators are only defined between the types they declare, so `1 + 1.0` is invalid
+            // rather than a failed cast
+            let out = lval
+                .get_op(op)
+                .filter(|f| operands_agree(&f.arg_tys(), &[lval.ty(), rval.ty()]))
+                .ok_or_else(|| ExceptionKind::InvalidOp(op, lval.ty(), Some(rval.ty())))?
+                .invoke(env, vec![lval, rval])?;
+            stack.push(out);
+        }
+        Instr::GetField(n) => {
+            let val = pop(stack);
+            stack.push(val.get_field(chunk.name(n))?);
+        }
+        Instr::SetField(n) => {
+            let val = pop(stack);
+            let target = pop(stack);
+            stack.push(target.set_field(env, chunk.name(n), val)?);
+        }
+        Instr::Index => {
+            let idx = pop(stack).downcast::<Int>()?.val();
+            let val = pop(stack);
+            stack.push(val.get_index(idx)?);
+        }
+        Instr::Call(argc) => {
+            let args = stack.split_off(stack.len() - argc as usize);
+            let callee = pop(stack);
+            let out = callee.downcast::<Fn<'_>>()?.invoke(env, args)?;
+            stack.push(out);
+        }
+        Instr::Pop => {
+            pop(stack);
+        }
+        Instr::Jump(target) => *pc = target as usize,
+        Instr::JumpIfNotFirst(target) => {
+            if !env.is_first_iter() {
+                *pc = target as usize;
+            }
+        }
+        Instr::ShortCircuit(when, target) => {
+            if let Ok(bit) = top(stack).downcast::<Bit>() {
+                if bit.val() == when {
+                    *pc = target as usize;
+                }
+            }
+        }
+        Instr::SyncStart => env.set_sync(true),
+        Instr::SyncEnd => env.set_sync(false),
+        Instr::CheckLimit => {
+            let val = pop(stack);
+            if let Some(Callee { proto, ret }) = func {
+                if !env.is_sync() && limit_reached(env, &proto.limit)? {
+                    if let Err(e) = ret.validate(env, &val) {
+                        return Err(e.with_label(
+                            Location::new(proto.source(), proto.ret.span.clone()),
+                            String::from("return type declared here"),
+                        ));
+                    }
+                    return Ok(Some(val));
+                }
+            }
+        }
+        Instr::NextIter => env.next_iter(),
+        Instr::Return => return Ok(Some(pop(stack))),
+    }
+
+    Ok(None)
+}
+
+/// Whether an operator declared with `declared` operands can be applied to `actual` ones
+fn operands_agree(declared: &[Type], actual: &[Type]) -> bool {
+    declared.len() == actual.len() && declared.iter().zip(actual).all(|(a, b)| a.agrees(b))
+}
+
+fn top<'a, 'ip>(stack: &'a [Value<'ip>]) -> &'a Value<'ip> {
+    stack.last().expect("VM stack underflow")
+}
+
+fn pop<'ip>(stack: &mut Vec<Value<'ip>>) -> Value<'ip> {
+    stack.pop().expect("VM stack underflow")
+}
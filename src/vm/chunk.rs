@@ -0,0 +1,207 @@
+use core::fmt;
+use std::rc::Rc;
+
+use crate::ast::{Ident, Span, Spanned, Type};
+use crate::diagnostic::{Location, SourceId};
+use crate::interp::{BigInt, Bit, Char, CharArray, Float, Int, Op, Value};
+
+/// A single VM instruction. Operands index into the pools of the [`Chunk`] containing it.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Instr {
+    /// Push a constant from the constant pool
+    Const(u32),
+    /// Push null
+    Null,
+    /// Push the value of a named variable
+    Load(u32),
+    /// Define a variable in the innermost scope, leaving the value on the stack
+    Define(u32),
+    /// Like `Define`, but only on the first iteration of the current frame. On later iterations
+    /// the value is replaced with null.
+    DefineFirst(u32),
+    /// Assign an existing variable in the scope it was defined in, which must keep its type, leaving
+    /// the value on the stack
+    Assign(u32),
+    /// Bind a type from the type pool to a name
+    DefineType(u32, u32),
+    /// Push a new function value from the prototype pool
+    MakeFn(u32),
+    /// Pop the given number of values, pushing an array of them
+    MakeArray(u32),
+    /// Pop a value for each field in a field list, pushing a record of the named type
+    MakeRecord(u32, u32),
+    /// Apply a unary operator to the top of the stack
+    UnOp(Op),
+    /// Apply a binary operator to the top two values of the stack
+    BinOp(Op),
+    /// Replace the top of the stack with one of its fields
+    GetField(u32),
+    /// Pop a value, and replace the top of the stack with a copy that has the field set to it
+    SetField(u32),
+    /// Pop an int, and replace the top of the stack with the element at that index
+    Index,
+    /// Call the function below the given number of arguments
+    Call(u32),
+    /// Discard the top of the stack
+    Pop,
+    /// Continue from the given instruction
+    Jump(u32),
+    /// Continue from the given instruction, unless this is the first iteration of the frame
+    JumpIfNotFirst(u32),
+    /// Continue from the given instruction if the top of the stack is a bit with the given value,
+    /// leaving it as the result of a short-circuiting `&&` or `||`
+    ShortCircuit(bool, u32),
+    /// Start a region in which limits aren't checked
+    SyncStart,
+    /// End a region started by `SyncStart`
+    SyncEnd,
+    /// Pop the value of the last statement, and return it if the frame's limit is reached
+    CheckLimit,
+    /// Mark the end of one iteration of the current frame
+    NextIter,
+    /// Return the top of the stack
+    Return,
+}
+
+/// A literal value stored in a chunk's constant pool
+#[derive(Clone, Debug, PartialEq)]
+pub enum Const {
+    Int(i128),
+    BigInt(num_bigint::BigInt),
+    Float(f64),
+    Char(char),
+    CharArray(String),
+    Bit(bool),
+}
+
+impl Const {
+    pub fn to_value<'ip>(&self) -> Value<'ip> {
+        match self {
+            Const::Int(i) => Value::new(Int::new(*i)),
+            Const::BigInt(i) => Value::new(BigInt::new(i.clone())),
+            Const::Float(f) => Value::new(Float::new(*f)),
+            Const::Char(c) => Value::new(Char::new(*c)),
+            Const::CharArray(s) => Value::new(CharArray::new(s.clone())),
+            Const::Bit(b) => Value::new(Bit::new(*b)),
+        }
+    }
+}
+
+/// A compiled sequence of instructions, along with the pools they refer to
+#[derive(Clone, Debug, Default)]
+pub struct Chunk {
+    pub(crate) source: SourceId,
+    pub(crate) code: Vec<Instr>,
+    /// The span of source each instruction was compiled from
+    pub(crate) spans: Vec<Span>,
+    pub(crate) consts: Vec<Const>,
+    pub(crate) names: Vec<Ident>,
+    pub(crate) types: Vec<Type>,
+    /// The field names given to record constructors, in the order their values are pushed
+    pub(crate) field_lists: Vec<Vec<Ident>>,
+    pub(crate) protos: Vec<Rc<FnProto>>,
+}
+
+impl Chunk {
+    pub fn code(&self) -> &[Instr] {
+        &self.code
+    }
+
+    pub fn source(&self) -> SourceId {
+        self.source
+    }
+
+    /// The location of source the instruction at `pc` was compiled from
+    pub fn location(&self, pc: usize) -> Location {
+        Location::new(self.source, self.spans[pc].clone())
+    }
+
+    pub fn name(&self, idx: u32) -> &Ident {
+        &self.names[idx as usize]
+    }
+
+    pub fn constant(&self, idx: u32) -> &Const {
+        &self.consts[idx as usize]
+    }
+
+    pub fn ty(&self, idx: u32) -> &Type {
+        &self.types[idx as usize]
+    }
+
+    pub fn field_list(&self, idx: u32) -> &[Ident] {
+        &self.field_lists[idx as usize]
+    }
+
+    pub fn proto(&self, idx: u32) -> &Rc<FnProto> {
+        &self.protos[idx as usize]
+    }
+
+    fn disassemble(&self, f: &mut fmt::Formatter<'_>, indent: usize) -> fmt::Result {
+        for (idx, instr) in self.code.iter().enumerate() {
+            write!(f, "{:indent$}{:>4} ", "", idx, indent = indent)?;
+            match *instr {
+                Instr::Const(c) => writeln!(f, "Const {:?}", self.constant(c))?,
+                Instr::Load(n) => writeln!(f, "Load {}", &**self.name(n))?,
+                Instr::Define(n) => writeln!(f, "Define {}", &**self.name(n))?,
+                Instr::DefineFirst(n) => writeln!(f, "DefineFirst {}", &**self.name(n))?,
+                Instr::Assign(n) => writeln!(f, "Assign {}", &**self.name(n))?,
+                Instr::DefineType(n, t) => {
+                    writeln!(f, "DefineType {} {}", &**self.name(n), self.ty(t).pretty())?
+                }
+                Instr::MakeFn(p) => {
+                    let proto = self.proto(p);
+                    writeln!(f, "MakeFn {}", proto.name())?;
+                    writeln!(f, "{:indent$}     limit:", "", indent = indent)?;
+                    proto.limit.disassemble(f, indent + 8)?;
+                    writeln!(f, "{:indent$}     body:", "", indent = indent)?;
+                    proto.body.disassemble(f, indent + 8)?;
+                }
+                Instr::MakeRecord(n, l) => {
+                    let fields = self.field_list(l).iter().map(|f| &**f).collect::<Vec<_>>();
+                    writeln!(f, "MakeRecord {} {{ {} }}", &**self.name(n), fields.join(", "))?
+                }
+                Instr::GetField(n) => writeln!(f, "GetField {}", &**self.name(n))?,
+                Instr::SetField(n) => writeln!(f, "SetField {}", &**self.name(n))?,
+                Instr::UnOp(op) => writeln!(f, "UnOp {}", op.pretty())?,
+                Instr::BinOp(op) => writeln!(f, "BinOp {}", op.pretty())?,
+                _ => writeln!(f, "{:?}", instr)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for Chunk {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.disassemble(f, 0)
+    }
+}
+
+/// A compiled function definition or literal
+#[derive(Clone, Debug)]
+pub struct FnProto {
+    pub(crate) name: Ident,
+    pub(crate) generics: Vec<Ident>,
+    pub(crate) ret: Spanned<Type>,
+    pub(crate) args: Vec<(Ident, Spanned<Type>)>,
+    pub(crate) limit: Chunk,
+    pub(crate) body: Chunk,
+}
+
+impl FnProto {
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn ret_ty(&self) -> &Type {
+        &self.ret.node
+    }
+
+    pub fn arg_tys(&self) -> Vec<Type> {
+        self.args.iter().map(|(_, ty)| ty.node.clone()).collect()
+    }
+
+    pub fn source(&self) -> SourceId {
+        self.body.source
+    }
+}
@@ -0,0 +1,116 @@
+use goose::interp::{Bit, Int};
+use goose::{Error, Interpreter};
+
+fn bit(src: &str) -> bool {
+    Interpreter::new().run_str(src).unwrap().downcast::<Bit>().unwrap().val()
+}
+
+fn int(src: &str) -> i128 {
+    Interpreter::new().run_str(src).unwrap().downcast::<Int>().unwrap().val()
+}
+
+fn exception(src: &str) -> String {
+    match Interpreter::new().run_str(src) {
+        Err(Error::Exception(e)) => e.to_string(),
+        Err(e) => panic!("{}", e),
+        Ok(_) => panic!("`{}` didn't raise", src),
+    }
+}
+
+#[test]
+fn building_arrays() {
+    assert_eq!(int("len([1, 2, 3]);"), 3);
+    assert!(bit("push([1, 2], 3) == [1, 2, 3];"));
+    assert!(bit("push([], 'a') == ['a'];"));
+    assert!(bit("pop([1, 2, 3]) == [1, 2];"));
+    assert!(bit("slice([1, 2, 3, 4], 1, 3) == [2, 3];"));
+    assert!(bit("concat([1], [2, 3]) == [1, 2, 3];"));
+    assert!(bit("reverse([1, 2, 3]) == [3, 2, 1];"));
+    assert!(bit("contains([\"a\", \"b\"], \"b\");"));
+    assert!(bit("!contains([1, 2], 3);"));
+}
+
+#[test]
+fn originals_are_unchanged() {
+    assert!(bit("unique a = [1, 2]; unique b = push(a, 3); a == [1, 2];"));
+}
+
+#[test]
+fn higher_order() {
+    assert!(bit("map([1, 2, 3], fn: int (x: int) -> |1b| [ x * 10; ]) == [10, 20, 30];"));
+    assert!(bit("filter([1, 2, 3, 4], fn: bit (x: int) -> |1b| [ x > 2; ]) == [3, 4];"));
+    assert_eq!(int("fold([1, 2, 3, 4], 0, fn: int (acc: int, x: int) -> |1b| [ acc + x; ]);"), 10);
+}
+
+#[test]
+fn element_types_are_checked() {
+    assert_eq!(exception("push([1, 2], 'a');"), "Expected type `int`, got type `char`");
+    assert_eq!(exception("concat([1], [1.0]);"), "Expected type `int`, got type `float`");
+    assert_eq!(exception("contains([1], 'a');"), "Expected type `int`, got type `char`");
+    assert_eq!(
+        exception("map([1], fn: int (x: char) -> |1b| [ 0; ]);"),
+        "Expected type `char`, got type `int`",
+    );
+    assert_eq!(exception("push(5, 1);"), "Expected type `[any]`, got type `int`");
+    assert_eq!(exception("len(5);"), "Expected type `[any]`, got type `int`");
+}
+
+#[test]
+fn higher_order_types_are_checked_at_the_call() {
+    let src = "unique f = fn: int (x: chararray) -> |1b| [ 1; ];\nmap([1, 2], f);";
+    let e = match Interpreter::new().run_str(src) {
+        Err(Error::Exception(e)) => e,
+        r => panic!("{:?}", r.err()),
+    };
+    assert_eq!(e.to_string(), "Expected type `chararray`, got type `int`");
+    assert_eq!(e.location().unwrap().span.start, src.find("map").unwrap());
+
+    assert_eq!(
+        exception("filter([[1]], fn: bit (x: [char]) -> |1b| [ 1b; ]);"),
+        "Expected type `[char]`, got type `[int]`",
+    );
+    assert_eq!(
+        exception("fold([], \"a\", fn: int (acc: int, x: int) -> |1b| [ acc; ]);"),
+        "Expected type `int`, got type `chararray`",
+    );
+    assert_eq!(
+        exception("fold([1], 0, fn: chararray (acc: int, x: int) -> |1b| [ \"a\"; ]);"),
+        "Expected type `chararray`, got type `int`",
+    );
+    assert_eq!(
+        exception("fold([\"a\"], 0, fn: int (acc: int, x: int) -> |1b| [ acc; ]);"),
+        "Expected type `int`, got type `chararray`",
+    );
+    assert!(bit("map([], fn: int (x: chararray) -> |1b| [ 1; ]) == [];"));
+    assert_eq!(int("fold([[1], []], 0, fn: int (acc: int, x: [int]) -> |1b| [ acc + x.len; ]);"), 1);
+}
+
+#[test]
+fn out_of_range_raises() {
+    assert_eq!(exception("pop([]);"), "`pop` needs an array with at least one element, got an empty one");
+    assert_eq!(exception("slice([1, 2], 1, 3);"), "Index 3 is out of range for length 2");
+    assert_eq!(exception("slice([1, 2], 2, 1);"), "Index 2 is out of range for length 1");
+}
+
+#[test]
+fn empty_arrays_agree_with_any_array() {
+    assert!(!bit("[] == [1];"));
+    assert!(!bit("[1] == [];"));
+    assert!(bit("[] == [];"));
+    assert!(bit("[[]] != [[1]];"));
+    assert_eq!(int("unique a = [1, 2]; a = []; a = push(a, 3); (a + []).len;"), 1);
+}
+
+#[test]
+fn empty_arrays_dont_loosen_types() {
+    assert_eq!(exception("[[], [1], [\"a\"]];"), "Expected type `[int]`, got type `[chararray]`");
+    assert_eq!(
+        exception("push(push([[]], [1]), [\"a\"]);"),
+        "Expected type `[int]`, got type `[chararray]`",
+    );
+    assert_eq!(
+        exception("unique a = [1]; a = []; a = [\"x\"];"),
+        "Expected type `[int]`, got type `[chararray]`",
+    );
+    assert!(bit("[[], [1]] != [[2], []];"));
+}
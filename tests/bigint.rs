@@ -0,0 +1,103 @@
+use goose::interp::{BigInt, Bit, Int};
+use goose::{Error, Interpreter};
+
+fn bigint(src: &str) -> String {
+    let val = Interpreter::new().run_str(src).unwrap();
+    val.downcast::<BigInt>().unwrap().to_string()
+}
+
+fn bit(src: &str) -> bool {
+    Interpreter::new().run_str(src).unwrap().downcast::<Bit>().unwrap().val()
+}
+
+fn exception(src: &str) -> String {
+    match Interpreter::new().run_str(src) {
+        Err(Error::Exception(e)) => e.to_string(),
+        Err(e) => panic!("{}", e),
+        Ok(_) => panic!("`{}` didn't raise", src),
+    }
+}
+
+fn check(src: &str) -> Vec<String> {
+    let mut interp = Interpreter::new();
+    let id = interp.add_source("check.hnk", src);
+    let file = interp.parse(id).unwrap();
+    match interp.check(&file) {
+        Ok(()) => Vec::new(),
+        Err(Error::Check(diags)) => diags.iter().map(|d| d.message().to_string()).collect(),
+        Err(e) => panic!("{}", e),
+    }
+}
+
+#[test]
+fn literals() {
+    assert_eq!(bigint("1_000n;"), "1000");
+    assert_eq!(bigint("0xffn;"), "255");
+    assert_eq!(bigint("340282366920938463463374607431768211456n;"), "340282366920938463463374607431768211456");
+}
+
+#[test]
+fn arithmetic_doesnt_overflow() {
+    assert_eq!(
+        bigint("170141183460469231731687303715884105727n * 4n + 1n;"),
+        "680564733841876926926749214863536422909",
+    );
+    assert_eq!(bigint("-7n / 2n;"), "-3");
+    assert_eq!(bigint("!0n;"), "-1");
+    assert_eq!(bigint("6n & 3n | 8n ^ 1n;"), "11");
+    assert!(bit("10n > 9n && 2n == 2n && 2n != 3n;"));
+    assert_eq!(exception("1n / 0n;"), "Attempted to divide by zero");
+    assert!(exception("1n + 1;").starts_with("Attempted to invoke binary operator +"));
+}
+
+#[test]
+fn factorial() {
+    let src = "
+        def range: [int] (n: int) -> |len(out) == n| [
+            once {
+                carryover out = [1];
+            }
+            out = push(out, len(out) + 1);
+        ]
+        def factorial: bigint (n: int) -> |1b| [
+            fold(range(n), 1n, fn: bigint (acc: bigint, i: int) -> |1b| [ acc * to_bigint(i); ]);
+        ]
+        factorial(30);
+    ";
+    assert_eq!(bigint(src), "265252859812191058636308480000000");
+}
+
+#[test]
+fn conversions() {
+    let val = Interpreter::new().run_str("to_int(to_bigint(42) * 2n);").unwrap();
+    assert_eq!(val.downcast::<Int>().unwrap().val(), 84);
+    assert_eq!(
+        exception("to_int(340282366920938463463374607431768211456n);"),
+        "Value is out of range for type `int`",
+    );
+}
+
+#[test]
+fn type_aliases() {
+    let src = "
+        type Big = bigint;
+        def double: Big (x: Big) -> |1b| [ x * 2n; ]
+        double(21n);
+    ";
+    assert_eq!(bigint(src), "42");
+    assert!(check(src).is_empty());
+    assert_eq!(
+        exception("type Big = bigint; def f: Big (x: Big) -> |1b| [ x; ] f(1);"),
+        "Expected type `Big`, got type `int`",
+    );
+}
+
+#[test]
+fn write_output() {
+    let out = assert_cmd::Command::cargo_bin(env!("CARGO_PKG_NAME"))
+        .unwrap()
+        .arg("repl")
+        .write_stdin("2n * 170141183460469231731687303715884105727n;\n")
+        .unwrap();
+    assert_eq!(String::from_utf8(out.stdout).unwrap(), "340282366920938463463374607431768211454\n");
+}
@@ -0,0 +1,74 @@
+use goose::interp::{Bit, Char, CharArray, Int};
+use goose::{Error, Interpreter};
+
+fn bit(src: &str) -> bool {
+    Interpreter::new().run_str(src).unwrap().downcast::<Bit>().unwrap().val()
+}
+
+fn int(src: &str) -> i128 {
+    Interpreter::new().run_str(src).unwrap().downcast::<Int>().unwrap().val()
+}
+
+fn text(src: &str) -> String {
+    let val = Interpreter::new().run_str(src).unwrap();
+    val.downcast::<CharArray>().unwrap().to_string()
+}
+
+fn exception(src: &str) -> String {
+    match Interpreter::new().run_str(src) {
+        Err(Error::Exception(e)) => e.to_string(),
+        Err(e) => panic!("{}", e),
+        Ok(_) => panic!("`{}` didn't raise", src),
+    }
+}
+
+#[test]
+fn length_and_concat() {
+    assert_eq!(int("chararray_len(\"gänse\");"), 5);
+    assert_eq!(text("chararray_concat(\"ho\", \"nk\");"), "honk");
+    assert_eq!(
+        exception("chararray_concat(\"ho\", ['n']);"),
+        "Expected type `chararray`, got type `[char]`"
+    );
+}
+
+#[test]
+fn slicing() {
+    assert_eq!(text("substring(\"gänse\", 1, 3);"), "än");
+    let c = Interpreter::new().run_str("char_at(\"gänse\", 1);").unwrap();
+    assert_eq!(c.downcast::<Char>().unwrap().val(), 'ä');
+    assert_eq!(exception("char_at(\"abc\", 3);"), "Index 3 is out of range for length 3");
+    assert_eq!(exception("substring(\"abc\", 0, 4);"), "Index 4 is out of range for length 3");
+}
+
+#[test]
+fn splitting_and_joining() {
+    assert!(bit("split(\"a,b,,c\", \",\") == [\"a\", \"b\", \"\", \"c\"];"));
+    assert!(bit("split(\"ab\", \"\") == [\"a\", \"b\"];"));
+    assert_eq!(text("join([\"a\", \"b\", \"c\"], \", \");"), "a, b, c");
+    assert_eq!(text("join([], \", \");"), "");
+    assert_eq!(exception("join([1], \"\");"), "Expected type `chararray`, got type `int`");
+}
+
+#[test]
+fn transforming() {
+    assert_eq!(text("trim(\"  honk \t\");"), "honk");
+    assert_eq!(text("upper(\"Honk\");"), "HONK");
+    assert_eq!(text("lower(\"Honk\");"), "honk");
+    assert_eq!(text("replace(\"a-b-c\", \"-\", \"+\");"), "a+b+c");
+}
+
+#[test]
+fn searching() {
+    assert_eq!(int("find(\"gänse\", \"se\");"), 3);
+    assert_eq!(int("find(\"goose\", \"duck\");"), -1);
+    assert!(bit("starts_with(\"ERROR: disk full\", \"ERROR\");"));
+    assert!(bit("!ends_with(\"goose\", \"duck\");"));
+}
+
+#[test]
+fn char_arrays() {
+    assert!(bit("chars(\"abc\") == ['a', 'b', 'c'];"));
+    assert_eq!(text("from_chars(reverse(chars(\"abc\")));"), "cba");
+    assert_eq!(text("from_chars([]);"), "");
+}
@@ -0,0 +1,149 @@
+use goose::{Error, Interpreter};
+
+fn check(src: &str) -> Vec<String> {
+    let mut interp = Interpreter::new();
+    let id = interp.add_source("check.hnk", src);
+    let file = interp.parse(id).unwrap();
+    match interp.check(&file) {
+        Ok(()) => Vec::new(),
+        Err(Error::Check(diags)) => diags.iter().map(|d| d.message().to_string()).collect(),
+        Err(e) => panic!("{}", e),
+    }
+}
+
+#[test]
+fn valid_programs_pass() {
+    let src = "
+        def count: int (n: int) -> |i == n| [
+            once {
+                carryover i = 0;
+            }
+            i += 1;
+        ]
+        unique total = count(3) * 2;
+        write(console, total, \"done\");
+    ";
+    assert!(check(src).is_empty());
+}
+
+#[test]
+fn arity_and_argument_types() {
+    let src = "
+        def add: int (a: int, b: int) -> |1b| [ a + b; ]
+        add(1);
+        add(1, 'c');
+    ";
+    assert_eq!(
+        check(src),
+        [
+            "Function `add` expects 2 arguments, got 1",
+            "Expected type `int`, got type `char`",
+        ]
+    );
+}
+
+#[test]
+fn assignments_keep_their_type() {
+    assert_eq!(
+        check("unique x = 1;\nx = \"s\";"),
+        ["Expected type `int`, got type `chararray`"]
+    );
+    assert!(check("unique x = 1;\nunique x = \"s\";").is_empty());
+}
+
+#[test]
+fn return_types() {
+    assert_eq!(
+        check("def f: int () -> |1b| [ 'c'; ]"),
+        ["Expected type `int`, got type `char`"]
+    );
+    assert!(check("def f: int () -> |1b| [ 'c'; 1; ]").is_empty());
+}
+
+#[test]
+fn operators_and_limits() {
+    assert_eq!(
+        check("def f: int () -> |1| [ 1 + 1.0; ]"),
+        [
+            "Attempted to invoke binary operator + on invalid types. Left: `int`, Right: `float`",
+            "Expected type `bit`, got type `int`",
+        ]
+    );
+}
+
+#[test]
+fn globals_come_from_the_environment() {
+    let mut interp = Interpreter::new();
+    interp.run_str("def twice: int (a: int) -> |1b| [ a * 2; ]").unwrap();
+
+    let id = interp.add_source("check.hnk", "twice(1b);");
+    let file = interp.parse(id).unwrap();
+    assert!(matches!(interp.check(&file), Err(Error::Check(diags)) if diags.len() == 1));
+}
+
+#[test]
+fn wrong_arity_raises_at_runtime() {
+    let err = Interpreter::new()
+        .run_str("def f: int (a: int) -> |1b| [ a; ]\nf();")
+        .unwrap_err();
+    assert_eq!(err.to_string(), "Exception: Function `f` expects 1 arguments, got 0");
+}
+
+#[test]
+fn generic_builtins() {
+    assert!(check("unique n = len([1, 2]) + 1; unique a = map([1], fn: int (x: int) -> |1b| [ x; ]);").is_empty());
+    assert_eq!(check("unique n = len(5);"), ["Expected type `[any]`, got type `int`"]);
+    assert_eq!(
+        check("unique s = chararray_concat(\"a\", \"b\") + 1;"),
+        ["Attempted to invoke binary operator + on invalid types. Left: `chararray`, Right: `int`"]
+    );
+}
+
+#[test]
+fn empty_arrays() {
+    assert!(check("unique a = [1]; a = []; unique same = [] == a; unique b = [[], [1]];").is_empty());
+    assert_eq!(
+        check("unique a = [1] + ['c'];"),
+        ["Attempted to invoke binary operator + on invalid types. Left: `[int]`, Right: `[char]`"]
+    );
+    assert_eq!(check("unique a = [[], [1], [\"a\"]];"), ["Expected type `[int]`, got type `[chararray]`"]);
+    assert!(check("def f: [int] () -> |1b| [ []; ] def g: [[int]] () -> |1b| [ [[]]; ]").is_empty());
+    assert_eq!(
+        check("def f: [int] () -> |1b| [ [\"a\"]; ]"),
+        ["Expected type `[int]`, got type `[chararray]`"]
+    );
+}
+
+#[test]
+fn undeclared_names() {
+    assert_eq!(check("x = 5;"), ["Attempted to access invalid identifier x"]);
+    assert_eq!(check("write(console, nope);"), ["Attempted to access invalid identifier nope"]);
+    assert_eq!(
+        check("def f: int () -> |1b| [ y + 1; ]"),
+        ["Attempted to access invalid identifier y"]
+    );
+    assert_eq!(check("missing(1);"), ["Attempted to access invalid identifier missing"]);
+
+    // Functions run when they're called, so they can use names defined further down
+    let src = "
+        def even: bit (n: int) -> |1b| [ n == 0 || odd(n - 1); ]
+        def odd: bit (n: int) -> |1b| [ n != 0 && even(n - 1); ]
+        def total: int () -> |1b| [ base + 1; ]
+        unique base = 1;
+        unique shout = fn: int (n: int) -> |1b| [ shout(n); ];
+    ";
+    assert!(check(src).is_empty());
+}
+
+#[test]
+fn aliases_are_scoped_to_their_function() {
+    let src = "
+        type N = int;
+        def f: int () -> |1b| [
+            type N = chararray;
+            1;
+        ]
+        def g: N () -> |1b| [ 1; ]
+    ";
+    assert!(check(src).is_empty());
+}
@@ -0,0 +1,76 @@
+use goose::interp::{Bit, CharArray, Int};
+use goose::{Error, Interpreter};
+
+fn bit(src: &str) -> bool {
+    Interpreter::new().run_str(src).unwrap().downcast::<Bit>().unwrap().val()
+}
+
+fn int(src: &str) -> i128 {
+    Interpreter::new().run_str(src).unwrap().downcast::<Int>().unwrap().val()
+}
+
+fn text(src: &str) -> String {
+    Interpreter::new().run_str(src).unwrap().downcast::<CharArray>().unwrap().to_string()
+}
+
+fn exception(src: &str) -> String {
+    match Interpreter::new().run_str(src) {
+        Err(Error::Exception(e)) => e.to_string(),
+        Err(e) => panic!("{}", e),
+        Ok(_) => panic!("`{}` didn't raise", src),
+    }
+}
+
+#[test]
+fn between_numbers() {
+    assert!(bit("to_float(3) == 3.0;"));
+    assert_eq!(int("to_int(2.9);"), 2);
+    assert_eq!(int("to_int(-2.9);"), -2);
+    assert!(bit("to_bigint(2.5) == 2n;"));
+    assert!(bit("to_float(4n) == 4.0;"));
+    assert_eq!(int("to_int(1b) + to_int(0b);"), 1);
+    assert!(bit("to_bit(1) && !to_bit(0);"));
+    assert!(bit("to_float(1b) == 1.0;"));
+}
+
+#[test]
+fn chars_and_code_points() {
+    assert_eq!(int("to_int('a');"), 97);
+    assert!(bit("to_char(97) == 'a';"));
+    assert!(bit("to_char(\"x\") == 'x';"));
+    assert!(bit("to_bigint('a') == 97n;"));
+}
+
+#[test]
+fn to_text() {
+    assert_eq!(text("to_chararray(42);"), "42");
+    assert_eq!(text("to_chararray(-1.5);"), "-1.5");
+    assert_eq!(text("to_chararray(1b);"), "1b");
+    assert_eq!(text("to_chararray('c');"), "c");
+    assert_eq!(text("to_chararray([1, 2]);"), "[1, 2]");
+    assert_eq!(text("to_chararray(12n);"), "12");
+}
+
+#[test]
+fn from_text() {
+    assert_eq!(int("to_int(\"-12\");"), -12);
+    assert!(bit("to_float(\"2.5\") == 2.5;"));
+    assert!(bit("to_bigint(\"340282366920938463463374607431768211456\") == 340282366920938463463374607431768211456n;"));
+    assert!(bit("to_bigint(\"5n\") == 5n;"));
+    assert!(bit("to_bit(\"1b\");"));
+    assert!(bit("to_int(to_chararray(77)) == 77;"));
+}
+
+#[test]
+fn failures_raise() {
+    assert_eq!(exception("to_int(\"abc\");"), "Could not parse \"abc\" as type `int`");
+    assert_eq!(exception("to_char(\"ab\");"), "Could not parse \"ab\" as type `char`");
+    assert_eq!(exception("to_bit(\"yes\");"), "Could not parse \"yes\" as type `bit`");
+    assert_eq!(exception("to_int(1.0 / 0.0);"), "Value is out of range for type `int`");
+    assert_eq!(exception("to_int(1e40);"), "Value is out of range for type `int`");
+    assert_eq!(exception("to_bit(2);"), "Value is out of range for type `bit`");
+    assert_eq!(exception("to_char(-1);"), "Value is out of range for type `char`");
+    assert_eq!(exception("to_char(55296);"), "Value is out of range for type `char`");
+    assert_eq!(exception("to_float('a');"), "Cannot convert type `char` to type `float`");
+    assert_eq!(exception("to_int([1]);"), "Cannot convert type `[int]` to type `int`");
+}
@@ -0,0 +1,106 @@
+use goose::{Error, Interpreter};
+
+fn report(src: &str) -> String {
+    let mut interp = Interpreter::new();
+    let err = interp.run_str(src).unwrap_err();
+    let mut out = Vec::new();
+    interp.report(&err, false, &mut out).unwrap();
+    String::from_utf8(out).unwrap()
+}
+
+#[test]
+fn parse_errors_point_at_the_token() {
+    let out = report("unique a = 1;\nunique b = ;\n");
+    assert!(out.starts_with("Parse Failure:"), "{}", out);
+    assert!(out.contains("<input:0>:2:12"), "{}", out);
+    assert!(!out.contains('\x1b'), "{}", out);
+}
+
+#[test]
+fn assignment_labels_the_definition() {
+    let out = report("unique a = 1;\na = 'c';\n");
+    assert!(out.starts_with("Exception: Expected type `int`, got type `char`"), "{}", out);
+    assert!(out.contains("<input:0>:2:1"), "{}", out);
+    assert!(out.contains("variable first defined with type int here"), "{}", out);
+    assert!(!out.contains('\x1b'), "{}", out);
+}
+
+#[test]
+fn return_type_is_labelled() {
+    let out = report("def f: int () -> |1 == 1| [ 'c'; ]\nf();\n");
+    assert!(out.contains("<input:0>:1:29"), "{}", out);
+    assert!(out.contains("return type declared here"), "{}", out);
+}
+
+#[test]
+fn parameters_are_labelled() {
+    let out = report("def f: int (x: int) -> |1 == 1| [ x; ]\nf('c');\n");
+    assert!(out.contains("<input:0>:2:1"), "{}", out);
+    assert!(out.contains("parameter `x` declared here"), "{}", out);
+}
+
+#[test]
+fn exceptions_carry_a_trace() {
+    let src = "\
+def inner: int (x: int) -> |1 == 1| [ x = 'c'; ]
+def outer: int (x: int) -> |x == 3| [ x += 1; inner(x); ]
+unique f = fn: int () -> |1 == 1| [ outer(0); ];
+f();
+";
+    let mut interp = Interpreter::new();
+    let e = match interp.run_str(src) {
+        Err(Error::Exception(e)) => e,
+        other => panic!("{:?}", other.map(|v| v.ty())),
+    };
+
+    let trace = e
+        .trace()
+        .iter()
+        .map(|frame| (frame.name(), frame.iteration(), frame.call_site().map(|loc| loc.span.start)))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        trace,
+        [
+            ("inner", 0, Some(src.find("inner(x)").unwrap())),
+            ("outer", 0, Some(src.find("outer(0)").unwrap())),
+            ("<closure>", 0, Some(src.find("f();").unwrap())),
+        ]
+    );
+
+    let mut out = Vec::new();
+    interp.report(&Error::Exception(e), false, &mut out).unwrap();
+    let out = String::from_utf8(out).unwrap();
+    assert!(out.contains("in `outer` on iteration 0, called at <input:0>:3:37"), "{}", out);
+}
+
+#[test]
+fn parentheses_are_underlined() {
+    let out = report("(1b) + 1;\n");
+    let expected = "\
+Exception: Attempted to invoke binary operator + on invalid types. Left: `bit`, Right: `int`
+   ╭─[ <input:0>:1:1 ]
+   │
+ 1 │ (1b) + 1;
+   │ ────┬───  
+   │     ╰───── Attempted to invoke binary operator + on invalid types. Left: `bit`, Right: `int`
+───╯
+";
+    assert_eq!(out, expected);
+
+    let mut interp = Interpreter::new();
+    let id = interp.add_source("check.hnk", "unique a = [1, 2];\na#(1b);\n");
+    let file = interp.parse(id).unwrap();
+    let err = interp.check(&file).unwrap_err();
+    let mut out = Vec::new();
+    interp.report(&err, false, &mut out).unwrap();
+    let expected = "\
+Type Error: Expected type `int`, got type `bit`
+   ╭─[ check.hnk:2:3 ]
+   │
+ 2 │ a#(1b);
+   │   ──┬─  
+   │     ╰─── Expected type `int`, got type `bit`
+───╯
+";
+    assert_eq!(String::from_utf8(out).unwrap(), expected);
+}
@@ -0,0 +1,40 @@
+use goose::ast::Type;
+use goose::interp::{BuiltinFn, Int, Value};
+use goose::{Error, Interpreter};
+
+#[test]
+fn persistent_env() {
+    let mut interp = Interpreter::new();
+
+    interp.run_str("unique a = 1;").unwrap();
+    let val = interp.run_str("a += 2; a;").unwrap();
+
+    assert_eq!(**val.downcast::<Int>().unwrap(), 3);
+}
+
+#[test]
+fn host_functions_and_globals() {
+    let mut interp = Interpreter::new();
+
+    interp.set_global("answer", Value::new(Int::new(40)));
+    interp.register_fn(BuiltinFn::new(
+        "two",
+        Type::named("int"),
+        vec![],
+        |_env, _args| Ok(Value::new(Int::new(2))),
+    ));
+
+    let val = interp.run_str("answer + two();").unwrap();
+    assert_eq!(**val.downcast::<Int>().unwrap(), 42);
+}
+
+#[test]
+fn errors_leave_env_usable() {
+    let mut interp = Interpreter::new();
+
+    assert!(matches!(interp.run_str("unique a = ;"), Err(Error::Parse(_))));
+    assert!(matches!(interp.run_str("b;"), Err(Error::Exception(_))));
+
+    interp.run_str("unique a = 1;").unwrap();
+    assert!(interp.global("a").is_some());
+}
@@ -0,0 +1,72 @@
+use goose::interp::{Char, CharArray, Float, Int};
+use goose::{Error, Interpreter};
+
+fn int(src: &str) -> i128 {
+    Interpreter::new().run_str(src).unwrap().downcast::<Int>().unwrap().val()
+}
+
+fn exception(src: &str) -> String {
+    match Interpreter::new().run_str(src) {
+        Err(Error::Exception(e)) => e.to_string(),
+        Err(e) => panic!("{}", e),
+        Ok(_) => panic!("`{}` didn't raise", src),
+    }
+}
+
+fn check(src: &str) -> Vec<String> {
+    let mut interp = Interpreter::new();
+    let id = interp.add_source("check.hnk", src);
+    let file = interp.parse(id).unwrap();
+    match interp.check(&file) {
+        Ok(()) => Vec::new(),
+        Err(Error::Check(diags)) => diags.iter().map(|d| d.message().to_string()).collect(),
+        Err(e) => panic!("{}", e),
+    }
+}
+
+#[test]
+fn indexing() {
+    assert_eq!(int("[10, 20, 30]#1;"), 20);
+    assert_eq!(int("unique a = [[1, 2], [3, 4]]; unique i = 0; a#(i + 1)#0;"), 3);
+    let c = Interpreter::new().run_str("\"honk\"#3;").unwrap();
+    assert_eq!(c.downcast::<Char>().unwrap().val(), 'k');
+}
+
+#[test]
+fn fields_of_literal_indexes() {
+    assert_eq!(int("unique a = [[1, 2], [3]]; a#0.len;"), 2);
+    assert_eq!(int("unique a = [[1, 2], [3]]; a#1.len + a#0#1;"), 3);
+    // A trailing dot still makes a float when nothing follows it
+    let f = Interpreter::new().run_str("10. + 1.5;").unwrap();
+    assert_eq!(f.downcast::<Float>().unwrap().val(), 11.5);
+}
+
+#[test]
+fn out_of_range_raises() {
+    assert_eq!(exception("[1, 2]#2;"), "Index 2 is out of range for length 2");
+    assert_eq!(exception("[1, 2]#(-1);"), "Index -1 is out of range for length 2");
+    assert_eq!(exception("[1, 2]#-1;"), "Index -1 is out of range for length 2");
+    assert_eq!(exception("\"\"#0;"), "Index 0 is out of range for length 0");
+    assert_eq!(exception("5#0;"), "Attempted to index into invalid type `int`");
+}
+
+#[test]
+fn builtin_fields() {
+    assert_eq!(int("[1, 2, 3].len;"), 3);
+    assert_eq!(int("\"goose\".len + 1;"), 6);
+    assert_eq!(int("def add: int (a: int, b: int) -> |1b| [ a + b; ] add.arity;"), 2);
+    let name = Interpreter::new()
+        .run_str("def add: int (a: int, b: int) -> |1b| [ a + b; ] add.name;")
+        .unwrap();
+    assert_eq!(name.downcast::<CharArray>().unwrap() as &str, "add");
+    assert_eq!(exception("unique n = 1; n.len;"), "Type `int` has no field `len`");
+}
+
+#[test]
+fn fields_are_checked() {
+    assert!(check("unique n = [1, 2].len + 1; unique c = \"ab\"#0 == 'a';").is_empty());
+    assert_eq!(
+        check("unique n = (1).len; unique c = [1]#'a';"),
+        ["Type `int` has no field `len`", "Expected type `int`, got type `char`"],
+    );
+}
@@ -0,0 +1,141 @@
+use std::fs;
+
+use goose::interp::{Bit, CharArray, Int};
+use goose::{Error, Interpreter};
+
+fn bit(src: &str) -> bool {
+    Interpreter::new().run_str(src).unwrap().downcast::<Bit>().unwrap().val()
+}
+
+fn int(src: &str) -> i128 {
+    Interpreter::new().run_str(src).unwrap().downcast::<Int>().unwrap().val()
+}
+
+fn text(src: &str) -> String {
+    Interpreter::new().run_str(src).unwrap().downcast::<CharArray>().unwrap().to_string()
+}
+
+fn exception(src: &str) -> String {
+    match Interpreter::new().run_str(src) {
+        Err(Error::Exception(e)) => e.to_string(),
+        Err(e) => panic!("{}", e),
+        Ok(_) => panic!("`{}` didn't raise", src),
+    }
+}
+
+/// A path only this test uses, holding `contents`
+fn scratch(name: &str, contents: &str) -> String {
+    let path = std::env::temp_dir().join(format!("goose-file-{}-{}", std::process::id(), name));
+    fs::write(&path, contents).unwrap();
+    path.display().to_string()
+}
+
+#[test]
+fn write_seek_and_read_back() {
+    let path = scratch("rw.txt", "old contents");
+    let src = format!(
+        r#"
+            unique f = open({:?}, "truncate");
+            write(f, "honk ", 42);
+            seek(f, 0);
+            read(f, all);
+        "#,
+        path
+    );
+    assert_eq!(text(&src), "honk 42");
+
+    let src = format!(
+        r#"
+            unique f = open({:?}, "write");
+            write(f, "HO");
+            tell(f);
+        "#,
+        path
+    );
+    assert_eq!(int(&src), 2);
+    // `write` doesn't truncate, so the rest of the file is left alone
+    assert_eq!(fs::read_to_string(&path).unwrap(), "HOnk 42");
+}
+
+#[test]
+fn a_handle_keeps_its_position() {
+    let path = scratch("lines.txt", "first\nsecond\n");
+    let src = format!(
+        r#"
+            unique f = open({:?}, "read");
+            unique a = read(f);
+            unique b = read(f, 3);
+            unique c = read(f);
+            a + b + c + read(f);
+        "#,
+        path
+    );
+    assert_eq!(text(&src), "first\nsecond\n");
+}
+
+#[test]
+fn append_mode() {
+    let path = scratch("append.txt", "a\n");
+    let src = format!(
+        r#"
+            unique f = open({:?}, "append");
+            write(f, "b\n");
+            flush(f);
+            close(f);
+        "#,
+        path
+    );
+    Interpreter::new().run_str(&src).unwrap();
+    assert_eq!(fs::read_to_string(&path).unwrap(), "a\nb\n");
+}
+
+#[test]
+fn closing() {
+    let path = scratch("closed.txt", "");
+    let src = format!(
+        r#"
+            unique f = open({:?}, "read");
+            unique g = f;
+            close(f);
+            g.open;
+        "#,
+        path
+    );
+    assert!(!bit(&src));
+
+    let src = format!("unique f = open({:?}, \"read\"); close(f); read(f);", path);
+    assert_eq!(exception(&src), format!("Attempted to use {} after it was closed", path));
+    let src = format!("unique f = open({:?}, \"read\"); close(f); close(f);", path);
+    assert_eq!(exception(&src), format!("Attempted to use {} after it was closed", path));
+}
+
+#[test]
+fn identity() {
+    let path = scratch("same.txt", "");
+    let src = format!(
+        r#"
+            unique f = open({:?}, "read");
+            unique g = f;
+            f == g && f != open({:?}, "read");
+        "#,
+        path, path
+    );
+    assert!(bit(&src));
+    assert_eq!(
+        text(&format!("to_chararray(open({:?}, \"read\"));", path)),
+        format!("<file {}>", path)
+    );
+}
+
+#[test]
+fn invalid_handles() {
+    assert_eq!(exception("write(1000, \"x\");"), "1000 is not an open file descriptor");
+    assert_eq!(exception("read(-1);"), "-1 is not an open file descriptor");
+    assert_eq!(
+        exception("open(\"x\", \"rw\");"),
+        "Expected a mode of `read`, `write`, `append` or `truncate`, got \"rw\""
+    );
+    assert_eq!(exception("open(\"/nonexistent/goose\", \"read\");"), "IO operation failed");
+    assert_eq!(exception("close(1);"), "Expected type `file`, got type `int`");
+    assert_eq!(exception("write(1.5, 1);"), "Expected type `file`, got type `float`");
+}
@@ -0,0 +1,126 @@
+use goose::interp::{Bit, Int};
+use goose::{Error, Interpreter};
+
+fn bit(src: &str) -> bool {
+    Interpreter::new().run_str(src).unwrap().downcast::<Bit>().unwrap().val()
+}
+
+fn int(src: &str) -> i128 {
+    Interpreter::new().run_str(src).unwrap().downcast::<Int>().unwrap().val()
+}
+
+fn exception(src: &str) -> String {
+    match Interpreter::new().run_str(src) {
+        Err(Error::Exception(e)) => e.to_string(),
+        Err(e) => panic!("{}", e),
+        Ok(_) => panic!("`{}` didn't raise", src),
+    }
+}
+
+fn check(src: &str) -> Vec<String> {
+    let mut interp = Interpreter::new();
+    let id = interp.add_source("check.hnk", src);
+    let file = interp.parse(id).unwrap();
+    match interp.check(&file) {
+        Ok(()) => Vec::new(),
+        Err(Error::Check(diags)) => diags.iter().map(|d| d.message().to_string()).collect(),
+        Err(e) => panic!("{}", e),
+    }
+}
+
+const IDENTITY: &str = "def identity<T>: T (x: T) -> |1b| [ x; ]\n";
+const PICK: &str = "def pick<T>: T (xs: [T], i: int) -> |1b| [ xs#i; ]\n";
+
+#[test]
+fn type_parameters() {
+    assert_eq!(int(&format!("{}identity(5);", IDENTITY)), 5);
+    assert!(bit(&format!("{}identity('a') == 'a';", IDENTITY)));
+    assert!(bit(&format!("{}identity([1, 2]) == [1, 2];", IDENTITY)));
+    assert_eq!(int(&format!("{}pick([1, 2], 1);", PICK)), 2);
+}
+
+#[test]
+fn parameters_bind_consistently() {
+    let pair = "def same<T>: bit (a: T, b: T) -> |1b| [ a == b; ]\n";
+    assert!(bit(&format!("{}same(1, 1);", pair)));
+    assert_eq!(exception(&format!("{}same(1, 'a');", pair)), "Expected type `int`, got type `char`");
+    let first = "def first<T>: T (xs: [T]) -> |1b| [ xs#0; ]\n";
+    assert_eq!(int(&format!("{}first([3, 4]);", first)), 3);
+    assert_eq!(exception(&format!("{}first(3);", first)), "Expected type `[T]`, got type `int`");
+}
+
+#[test]
+fn return_type_uses_bindings() {
+    let src = "def wrong<T>: T (x: T) -> |1b| [ 'c'; ] wrong(1);";
+    assert_eq!(exception(src), "Expected type `int`, got type `char`");
+}
+
+#[test]
+fn generic_closures() {
+    let src = "
+        def apply: int (f: fn: int (int), x: int) -> |1b| [ f(x); ]
+        unique id = fn<T>: T (x: T) -> |1b| [ x; ];
+        apply(id, 7) + map([1, 2], id)#1;
+    ";
+    assert_eq!(int(src), 9);
+}
+
+#[test]
+fn generic_functions_are_instantiated_where_passed() {
+    let src = "
+        def apply: int (f: fn: int (chararray), s: chararray) -> |1b| [ f(s); ]
+        def id<T>: T (x: T) -> |1b| [ x; ]
+        apply(id, \"honk\");
+    ";
+    assert_eq!(exception(src), "Expected type `fn: int (chararray)`, got type `fn: T (T)`");
+
+    let src = "
+        def apply: chararray (f: fn: chararray (chararray), s: chararray) -> |1b| [ f(s); ]
+        def id<T>: T (x: T) -> |1b| [ x; ]
+        apply(id, \"honk\");
+    ";
+    assert!(Interpreter::new().run_str(src).is_ok());
+
+    // A parameter of the function's own binds once, so both of its uses must agree
+    let src = "
+        def pair: bit (f: fn: bit (int, char)) -> |1b| [ f(1, 'a'); ]
+        def same<T>: bit (a: T, b: T) -> |1b| [ a == b; ]
+        pair(same);
+    ";
+    assert_eq!(exception(src), "Expected type `fn: bit (int char)`, got type `fn: bit (T T)`");
+}
+
+#[test]
+fn closures_see_type_parameters() {
+    let src = "
+        def last<T>: T (xs: [T], init: T) -> |1b| [
+            fold(xs, init, fn: T (acc: T, x: T) -> |1b| [ x; ]);
+        ]
+        last([1, 2, 3], 0);
+    ";
+    assert_eq!(int(src), 3);
+}
+
+#[test]
+fn checked_any() {
+    let src = "def describe: chararray (x: any) -> |1b| [ to_chararray(x); ] describe(1b) + describe(2);";
+    let val = Interpreter::new().run_str(src).unwrap();
+    assert_eq!(val.downcast::<goose::interp::CharArray>().unwrap().to_string(), "1b2");
+    assert_eq!(int("def count: int (xs: [any]) -> |1b| [ len(xs); ] count(['a', 'b']);"), 2);
+    assert_eq!(
+        exception("def count: int (xs: [any]) -> |1b| [ len(xs); ] count(1);"),
+        "Expected type `[any]`, got type `int`",
+    );
+}
+
+#[test]
+fn generics_are_checked() {
+    assert!(check(&format!("{}unique n = identity(1) + 1; identity('a');", IDENTITY)).is_empty());
+    assert_eq!(
+        check("def add<T>: T (a: T, b: T) -> |1b| [ a + b; ]"),
+        ["Attempted to invoke binary operator + on invalid types. Left: `T`, Right: `T`"],
+    );
+    let mut interp = Interpreter::new();
+    let id = interp.add_source("dup.hnk", "def f<T, T>: T (x: T) -> |1b| [ x; ]");
+    assert!(interp.parse(id).is_err());
+}
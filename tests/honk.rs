@@ -0,0 +1,99 @@
+use std::fs;
+
+use assert_cmd::Command;
+use goose::Interpreter;
+
+/// A path only this test uses, holding `contents`
+fn scratch(name: &str, contents: &str) -> String {
+    let path = std::env::temp_dir().join(format!("goose-honk-{}-{}", std::process::id(), name));
+    fs::write(&path, contents).unwrap();
+    path.display().to_string()
+}
+
+#[test]
+fn redirected_to_memory() {
+    let mut interp = Interpreter::new();
+    interp.env().honk().to_memory().unwrap();
+
+    interp.run_str("write(honk, \"Honk \", 1);").unwrap();
+    interp.run_str("write_honk(\"!\");").unwrap();
+
+    assert_eq!(interp.env().honk().memory(), Some(&b"Honk 1!"[..]));
+    assert_eq!(interp.env().honk().path(), None);
+}
+
+#[test]
+fn buffered_until_flushed() {
+    let path = scratch("buffered", "stale");
+    let mut interp = Interpreter::new();
+    interp.env().honk().open(&path).unwrap();
+    assert_eq!(fs::read_to_string(&path).unwrap(), "");
+
+    interp.run_str("write(honk, \"Honk!\");").unwrap();
+    assert_eq!(fs::read_to_string(&path).unwrap(), "");
+
+    interp.env().honk().flush().unwrap();
+    assert_eq!(fs::read_to_string(&path).unwrap(), "Honk!");
+}
+
+#[test]
+fn truncated_once_per_run() {
+    let path = scratch("flag", "stale");
+    let script = scratch("flag.hnk", "write(honk, \"a\"); write(honk, \"b\");");
+
+    Command::cargo_bin(env!("CARGO_PKG_NAME"))
+        .unwrap()
+        .args(["--honk", &path, &script])
+        .unwrap();
+    assert_eq!(fs::read_to_string(&path).unwrap(), "ab");
+
+    // Only the output of the latest run is kept
+    Command::cargo_bin(env!("CARGO_PKG_NAME"))
+        .unwrap()
+        .args(["--honk", &path, &script])
+        .unwrap();
+    assert_eq!(fs::read_to_string(&path).unwrap(), "ab");
+}
+
+#[test]
+fn truncated_without_writes() {
+    let path = scratch("empty", "stale");
+    let script = scratch("empty.hnk", "write(console, 1);");
+
+    Command::cargo_bin(env!("CARGO_PKG_NAME"))
+        .unwrap()
+        .arg(&script)
+        .env("GOOSE_HONK", &path)
+        .unwrap();
+    assert_eq!(fs::read_to_string(&path).unwrap(), "");
+}
+
+#[test]
+fn default_truncated_without_writes() {
+    let dir = std::env::temp_dir().join(format!("goose-honk-{}-default", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    fs::write(dir.join("honk"), "stale").unwrap();
+    let script = scratch("default.hnk", "write(console, 1);");
+
+    Command::cargo_bin(env!("CARGO_PKG_NAME"))
+        .unwrap()
+        .arg(&script)
+        .current_dir(&dir)
+        .env_remove("GOOSE_HONK")
+        .unwrap();
+    assert_eq!(fs::read_to_string(dir.join("honk")).unwrap(), "");
+}
+
+#[test]
+fn flushed_after_an_exception() {
+    let path = scratch("exception", "");
+    let script = scratch("exception.hnk", "write(honk, \"before\"); 1 / 0;");
+
+    Command::cargo_bin(env!("CARGO_PKG_NAME"))
+        .unwrap()
+        .arg(&script)
+        .env("GOOSE_HONK", &path)
+        .assert()
+        .failure();
+    assert_eq!(fs::read_to_string(&path).unwrap(), "before");
+}
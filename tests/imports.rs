@@ -0,0 +1,295 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use assert_cmd::Command;
+use goose::interp::{CharArray, Int};
+use goose::{Error, Interpreter};
+
+/// A directory only this test uses, holding the given files
+fn project(name: &str, files: &[(&str, &str)]) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("goose-imports-{}-{}", std::process::id(), name));
+    for (path, src) in files {
+        let path = dir.join(path);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, src).unwrap();
+    }
+    dir
+}
+
+fn run(interp: &mut Interpreter, path: &Path) -> Result<goose::interp::Value<'static>, Error> {
+    interp.run_file(path)
+}
+
+fn int(path: &Path) -> i128 {
+    **run(&mut Interpreter::new(), path).unwrap().downcast::<Int>().unwrap()
+}
+
+fn exception(path: &Path) -> goose::interp::Exception {
+    match run(&mut Interpreter::new(), path) {
+        Err(Error::Exception(e)) => e,
+        Err(e) => panic!("{}", e),
+        Ok(_) => panic!("`{}` didn't raise", path.display()),
+    }
+}
+
+fn parse_errors(src: &str) -> Vec<String> {
+    let mut interp = Interpreter::new();
+    match interp.run_str(src) {
+        Err(Error::Parse(errs)) => errs.iter().map(|e| e.message().to_string()).collect(),
+        Err(e) => panic!("{}", e),
+        Ok(_) => panic!("`{}` parsed", src),
+    }
+}
+
+const MATH: &str = "
+    def square: int (n: int) -> |1b| [ n * n; ]
+    unique ten = 10;
+    write(honk, \"loaded\");
+";
+
+#[test]
+fn namespaces() {
+    let dir = project(
+        "namespaces",
+        &[
+            ("math.hnk", MATH),
+            ("main.hnk", "import \"math.hnk\"; import \"math\" as m; math.square(3) + m.ten;"),
+        ],
+    );
+    assert_eq!(int(&dir.join("main.hnk")), 19);
+}
+
+#[test]
+fn selective() {
+    let dir = project(
+        "selective",
+        &[
+            ("math.hnk", MATH),
+            ("main.hnk", "import { square as sq, ten } from \"math.hnk\"; sq(ten);"),
+            ("missing.hnk", "import { square, cube } from \"math.hnk\";"),
+        ],
+    );
+    assert_eq!(int(&dir.join("main.hnk")), 100);
+    assert_eq!(
+        exception(&dir.join("missing.hnk")).to_string(),
+        "Module `math.hnk` has no definition `cube`"
+    );
+}
+
+#[test]
+fn each_file_runs_once() {
+    let dir = project(
+        "once",
+        &[
+            ("math.hnk", MATH),
+            ("stats.hnk", "import { square } from \"math.hnk\"; def var: int (x: int) -> |1b| [ square(x); ]"),
+            (
+                "main.hnk",
+                "import \"math.hnk\"; import \"stats.hnk\"; import { ten } from \"math.hnk\"; stats.var(ten);",
+            ),
+        ],
+    );
+    let mut interp = Interpreter::new();
+    interp.env().honk().to_memory().unwrap();
+    let val = run(&mut interp, &dir.join("main.hnk")).unwrap();
+    assert_eq!(**val.downcast::<Int>().unwrap(), 100);
+    assert_eq!(interp.env().honk().memory(), Some(&b"loaded"[..]));
+}
+
+#[test]
+fn relative_to_the_importer() {
+    let dir = project(
+        "relative",
+        &[
+            ("lib/greet.hnk", "import { name } from \"names.hnk\"; def greet: chararray () -> |1b| [ \"Honk, \" + name; ]"),
+            ("lib/names.hnk", "unique name = \"goose\";"),
+            ("main.hnk", "import \"lib/greet.hnk\"; greet.greet();"),
+        ],
+    );
+    let val = run(&mut Interpreter::new(), &dir.join("main.hnk")).unwrap();
+    assert_eq!(val.downcast::<CharArray>().unwrap().to_string(), "Honk, goose");
+}
+
+#[test]
+fn search_path() {
+    let dir = project("search", &[("shared/math.hnk", MATH)]);
+    let mut interp = Interpreter::new();
+    interp.add_search_path(dir.join("shared"));
+    let val = interp.run_str("import \"math\" as m; m.square(4);").unwrap();
+    assert_eq!(**val.downcast::<Int>().unwrap(), 16);
+
+    let e = match Interpreter::new().run_str("import \"math\" as m;") {
+        Err(Error::Exception(e)) => e,
+        _ => panic!("import without a search path didn't raise"),
+    };
+    assert_eq!(e.to_string(), "Couldn't find `math` to import");
+
+    let script = project("search-cli", &[("main.hnk", "import \"math\"; write(console, math.ten);")]);
+    Command::cargo_bin(env!("CARGO_PKG_NAME"))
+        .unwrap()
+        .args(["--path", &dir.join("shared").display().to_string()])
+        .arg(script.join("main.hnk"))
+        .assert()
+        .success()
+        .stdout("10");
+}
+
+#[test]
+fn definitions_stay_in_their_module() {
+    let dir = project(
+        "isolated",
+        &[
+            ("math.hnk", MATH),
+            ("main.hnk", "import \"math.hnk\"; square(2);"),
+        ],
+    );
+    assert_eq!(
+        exception(&dir.join("main.hnk")).to_string(),
+        "Attempted to access invalid identifier square"
+    );
+}
+
+#[test]
+fn cycles_point_at_both_imports() {
+    let dir = project(
+        "cycle",
+        &[
+            ("a.hnk", "import \"b.hnk\";"),
+            ("b.hnk", "import \"a.hnk\";"),
+            ("main.hnk", "import \"a.hnk\";"),
+        ],
+    );
+    let mut interp = Interpreter::new();
+    let err = run(&mut interp, &dir.join("main.hnk")).unwrap_err();
+    let e = match &err {
+        Error::Exception(e) => e,
+        e => panic!("{}", e),
+    };
+    assert_eq!(e.to_string(), "Import cycle: `a.hnk` ends up importing itself");
+
+    let sources = interp.sources();
+    let at = e.location().unwrap();
+    assert!(sources.name(at.source).ends_with("b.hnk"));
+    assert_eq!(&sources.text(at.source)[at.span.clone()], "import \"a.hnk\";");
+    let [(label, message)] = e.labels() else { panic!("expected one label") };
+    assert!(sources.name(label.source).ends_with("a.hnk"));
+    assert_eq!(message, "`b.hnk` is imported here");
+
+    let mut out = Vec::new();
+    interp.report(&err, false, &mut out).unwrap();
+    let out = String::from_utf8(out).unwrap();
+    assert!(out.contains("a.hnk:1:1") && out.contains("b.hnk:1:1"), "{}", out);
+}
+
+#[test]
+fn calling_any_expression() {
+    let mut interp = Interpreter::new();
+    let val = interp.run_str("unique fs = [fn: int (x: int) -> |1b| [ x + 1; ]]; fs#0(2);").unwrap();
+    assert_eq!(**val.downcast::<Int>().unwrap(), 3);
+}
+
+#[test]
+fn import_syntax() {
+    assert_eq!(
+        parse_errors("write(console, 1); import \"math.hnk\";"),
+        ["Imports must come before any other statement"]
+    );
+    assert_eq!(
+        parse_errors("import \"my-lib.hnk\";"),
+        ["`my-lib.hnk` isn't a valid name, so the import needs one given with `as`"]
+    );
+    assert_eq!(
+        parse_errors("import { a, b as a } from \"x.hnk\";"),
+        ["Name `a` is given more than once"]
+    );
+}
+
+#[test]
+fn importing_itself() {
+    let dir = project("itself", &[("main.hnk", "import \"main.hnk\" as me; write(honk, \"ran\");")]);
+    let mut interp = Interpreter::new();
+    interp.env().honk().to_memory().unwrap();
+    let e = match run(&mut interp, &dir.join("main.hnk")) {
+        Err(Error::Exception(e)) => e,
+        r => panic!("{:?}", r.err()),
+    };
+    assert_eq!(e.to_string(), "Import cycle: `main.hnk` ends up importing itself");
+    assert_eq!(interp.env().honk().memory(), Some(&b""[..]));
+
+    let mut interp = Interpreter::new();
+    let path = dir.join("main.hnk");
+    let id = interp.add_source(&path.display().to_string(), &fs::read_to_string(&path).unwrap());
+    let file = interp.parse(id).unwrap();
+    assert_eq!(check(&mut interp, &file), ["Import cycle: `main.hnk` ends up importing itself"]);
+}
+
+fn check(interp: &mut Interpreter, file: &goose::ast::File) -> Vec<String> {
+    match interp.check(file) {
+        Err(Error::Check(diags)) => diags.iter().map(|d| d.message().to_string()).collect(),
+        r => panic!("{:?}", r.err()),
+    }
+}
+
+fn check_file(path: &Path) -> Vec<String> {
+    let mut interp = Interpreter::new();
+    let id = interp.add_source(&path.display().to_string(), &fs::read_to_string(path).unwrap());
+    let file = interp.parse(id).unwrap();
+    check(&mut interp, &file)
+}
+
+#[test]
+fn imports_are_checked() {
+    let dir = project(
+        "checked",
+        &[
+            ("math.hnk", MATH),
+            ("bad.hnk", "def one: int () -> |1b| [ \"one\"; ]"),
+            (
+                "main.hnk",
+                "
+                import \"math.hnk\" as m;
+                import { square, ten } from \"math.hnk\";
+                import { cube } from \"math.hnk\";
+                import \"bad.hnk\";
+                m.square(\"3\");
+                square(ten, 2);
+                m.ten + \"1\";
+                m.nope;
+                ",
+            ),
+        ],
+    );
+    assert_eq!(
+        check_file(&dir.join("main.hnk")),
+        [
+            "Expected type `int`, got type `chararray`",
+            "Module `math.hnk` has no definition `cube`",
+            "Expected type `int`, got type `chararray`",
+            "Function `square` expects 1 arguments, got 2",
+            "Attempted to invoke binary operator + on invalid types. Left: `int`, Right: `chararray`",
+            "Module `math.hnk` has no definition `nope`",
+        ]
+    );
+}
+
+#[test]
+fn missing_imports_are_reported_when_checking() {
+    let mut interp = Interpreter::new();
+    let src = "
+        import \"nowhere.hnk\" as m;
+        import { f } from \"nowhere.hnk\";
+        m.g(1) + f(2);
+        unique fs = [fn: int (x: int) -> |1b| [ x + 1; ]];
+        fs#0(1, 2);
+    ";
+    let id = interp.add_source("check.hnk", src);
+    let file = interp.parse(id).unwrap();
+    assert_eq!(
+        check(&mut interp, &file),
+        [
+            "Couldn't find `nowhere.hnk` to import",
+            "Couldn't find `nowhere.hnk` to import",
+            "Function `<fn>` expects 1 arguments, got 2",
+        ]
+    );
+}
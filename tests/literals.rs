@@ -0,0 +1,119 @@
+use goose::interp::{Bit, Char, CharArray, Float, Int};
+use goose::{Error, Interpreter};
+
+fn text(src: &str) -> String {
+    let val = Interpreter::new().run_str(src).unwrap();
+    val.downcast::<CharArray>().unwrap().to_string()
+}
+
+fn char(src: &str) -> char {
+    Interpreter::new().run_str(src).unwrap().downcast::<Char>().unwrap().val()
+}
+
+fn int(src: &str) -> i128 {
+    Interpreter::new().run_str(src).unwrap().downcast::<Int>().unwrap().val()
+}
+
+fn float(src: &str) -> f64 {
+    Interpreter::new().run_str(src).unwrap().downcast::<Float>().unwrap().val()
+}
+
+/// The span and message of the first parse error
+fn parse_error(src: &str) -> (std::ops::Range<usize>, String) {
+    match Interpreter::new().run_str(src) {
+        Err(Error::Parse(errs)) => (errs[0].span(), errs[0].message().to_string()),
+        Err(e) => panic!("{}", e),
+        Ok(_) => panic!("`{}` parsed", src),
+    }
+}
+
+#[test]
+fn string_escapes() {
+    assert_eq!(text(r#""a\nb\tc\r";"#), "a\nb\tc\r");
+    assert_eq!(text(r#""\"quoted\" \\ \'";"#), "\"quoted\" \\ '");
+    assert_eq!(text(r#""nul\0";"#), "nul\0");
+    assert_eq!(text(r#""\u{48}\u{1F600}";"#), "H\u{1F600}");
+}
+
+#[test]
+fn char_escapes() {
+    assert_eq!(char(r"'\n';"), '\n');
+    assert_eq!(char(r"'\'';"), '\'');
+    assert_eq!(char(r"'\\';"), '\\');
+    assert_eq!(char(r"'\u{e9}';"), 'é');
+    assert_eq!(char("'ä';"), 'ä');
+}
+
+#[test]
+fn invalid_escapes_are_reported() {
+    assert_eq!(
+        parse_error(r#"unique s = "a\qb";"#),
+        (13..15, String::from("Invalid escape sequence `\\q`")),
+    );
+    assert_eq!(
+        parse_error(r#""\u{110000}";"#),
+        (1..11, String::from("`110000` is not a valid unicode code point")),
+    );
+    assert_eq!(
+        parse_error(r#""\u48";"#),
+        (1..3, String::from("Unicode escapes must be written like `\\u{1F600}`")),
+    );
+}
+
+#[test]
+fn chars_hold_one_char() {
+    let err = (0..4, String::from("A char literal must contain exactly one char"));
+    assert_eq!(parse_error("'ab';"), err);
+    assert_eq!(parse_error("'';").1, err.1);
+}
+
+#[test]
+fn integer_literals() {
+    assert_eq!(int("1_000_000;"), 1_000_000);
+    assert_eq!(int("0xff;"), 255);
+    assert_eq!(int("0xDEAD_beef;"), 0xdead_beef);
+    assert_eq!(int("0o17;"), 15);
+    assert_eq!(int("0b1010;"), 10);
+    assert_eq!(int("-170141183460469231731687303715884105727;"), -i128::MAX);
+}
+
+#[test]
+fn bits_are_not_binary() {
+    let val = Interpreter::new().run_str("0b;").unwrap();
+    assert!(!val.downcast::<Bit>().unwrap().val());
+    let val = Interpreter::new().run_str("1b;").unwrap();
+    assert!(val.downcast::<Bit>().unwrap().val());
+    assert_eq!(int("0b1;"), 1);
+}
+
+#[test]
+fn separators_go_between_digits() {
+    let misplaced = |lit: &str| format!("`{}` has a `_` that isn't between two digits", lit);
+    for lit in ["1_", "1__0", "0x_ff", "0b1_", "1_n", "1_.5", "1._5", "1e3_"] {
+        assert_eq!(parse_error(&format!("{};", lit)), (0..lit.len(), misplaced(lit)));
+    }
+}
+
+#[test]
+fn float_literals() {
+    assert_eq!(float(".5;"), 0.5);
+    assert_eq!(float("10.;"), 10.0);
+    assert_eq!(float("1_000.25;"), 1000.25);
+    assert_eq!(float("1e3;"), 1000.0);
+    assert_eq!(float("2.5E-2;"), 0.025);
+    assert_eq!(float(".5e1;"), 5.0);
+}
+
+#[test]
+fn out_of_range_literals_are_reported() {
+    assert_eq!(
+        parse_error("unique a = 170141183460469231731687303715884105728;"),
+        (11..50, String::from("`170141183460469231731687303715884105728` is too large to fit in an int")),
+    );
+    assert_eq!(
+        parse_error("0x1_0000_0000_0000_0000_0000_0000_0000_0000;").1,
+        "`0x1_0000_0000_0000_0000_0000_0000_0000_0000` is too large to fit in an int",
+    );
+    assert_eq!(parse_error("1e400;").1, "`1e400` is too large to fit in a float");
+    assert_eq!(parse_error("0x_;").1, "`0x_` has no digits");
+}
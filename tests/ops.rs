@@ -0,0 +1,143 @@
+use goose::interp::{Bit, Float, Int};
+use goose::{Error, Interpreter};
+
+fn bit(src: &str) -> bool {
+    Interpreter::new().run_str(src).unwrap().downcast::<Bit>().unwrap().val()
+}
+
+fn int(src: &str) -> i128 {
+    Interpreter::new().run_str(src).unwrap().downcast::<Int>().unwrap().val()
+}
+
+fn exception(src: &str) -> String {
+    match Interpreter::new().run_str(src) {
+        Err(Error::Exception(e)) => e.to_string(),
+        Err(e) => panic!("{}", e),
+        Ok(_) => panic!("`{}` didn't raise", src),
+    }
+}
+
+#[test]
+fn int_arithmetic() {
+    assert_eq!(int("7 - 2 * 3;"), 1);
+    assert_eq!(int("-9 / 2;"), -4);
+    assert_eq!(int("!0;"), -1);
+}
+
+#[test]
+fn float_arithmetic() {
+    let val = Interpreter::new().run_str("-3.5 * 2.0 / 7.0 + 0.0;").unwrap();
+    assert_eq!(val.downcast::<Float>().unwrap().val(), -1.0);
+    assert!(bit("0.5 + 0.25 == 0.75;"));
+}
+
+#[test]
+fn equality() {
+    assert!(bit("unique a = 1 == 2; !a;"));
+    assert!(bit("1b == 1b;"));
+    assert!(bit("1b != 0b;"));
+    assert!(bit("'a' != 'b';"));
+    assert!(bit("\"ho\" + \"nk\" == \"honk\";"));
+    assert!(bit("[1, 2] + [3] == [1, 2, 3];"));
+    assert!(bit("[[1], [2]] != [[1], [3]];"));
+    assert!(bit("unique f = fn: int () -> |1 == 1| [ 1; ]; f == f;"));
+    assert!(bit("unique f = fn: int () -> |1 == 1| [ 1; ]; unique g = fn: int () -> |1 == 1| [ 1; ]; f != g;"));
+}
+
+#[test]
+fn unsupported_ops_raise() {
+    assert!(exception("1 + 1.0;").starts_with("Attempted to invoke binary operator +"));
+    assert!(exception("\"a\" * \"b\";").starts_with("Attempted to invoke binary operator *"));
+    assert!(exception("!1.0;").starts_with("Attempted to invoke unary operator !"));
+    assert_eq!(exception("1 / 0;"), "Attempted to divide by zero");
+}
+
+#[test]
+fn ordering() {
+    assert!(bit("1 < 2;"));
+    assert!(bit("2.5 >= 2.5;"));
+    assert!(bit("'a' < 'b';"));
+    assert!(bit("\"goose\" > \"gander\";"));
+    assert!(bit("unique a = 3 <= 2; !a;"));
+    assert!(exception("1 < 2.0;").starts_with("Attempted to invoke binary operator <"));
+}
+
+#[test]
+fn bitwise() {
+    assert_eq!(int("6 & 3;"), 2);
+    assert_eq!(int("6 | 3;"), 7);
+    assert_eq!(int("6 ^ 3;"), 5);
+    assert!(bit("1b ^ 0b;"));
+}
+
+#[test]
+fn precedence() {
+    assert_eq!(int("1 + 2 * 3;"), 7);
+    assert_eq!(int("(1 + 2) * 3;"), 9);
+    assert_eq!(int("1 | 2 & 3;"), 3);
+    assert!(bit("1 + 1 == 2 && 2 < 3;"));
+    assert!(bit("0b && 0b || 1b;"));
+}
+
+#[test]
+fn logical_ops_short_circuit() {
+    let src = "
+        def boom: bit () -> |1b| [ 1 / 0 == 0; ]
+        unique a = 0b && boom();
+        unique b = 1b || boom();
+        !a && b;
+    ";
+    assert!(bit(src));
+    assert_eq!(exception("1b && boom();"), "Attempted to access invalid identifier boom");
+    assert!(exception("1b && 1;").starts_with("Attempted to invoke binary operator &&"));
+}
+
+#[test]
+fn limits_allow_ordering_and_parenthesised_or() {
+    let src = "
+        def count: int (n: int) -> |i >= n && (i & 1 | 0) == 0| [
+            once {
+                carryover i = 0;
+            }
+            i += 1;
+        ]
+        count(3);
+    ";
+    assert_eq!(int(src), 4);
+}
+
+#[test]
+fn int_overflow_raises() {
+    let max = "170141183460469231731687303715884105727";
+    assert_eq!(exception(&format!("{} + 1;", max)), "Integer overflow in operator +");
+    assert_eq!(exception(&format!("-{} - 2;", max)), "Integer overflow in operator -");
+    assert_eq!(exception(&format!("{} * 2;", max)), "Integer overflow in operator *");
+    assert_eq!(exception(&format!("-(-{} - 1);", max)), "Integer overflow in operator -");
+    assert_eq!(exception(&format!("(-{} - 1) / -1;", max)), "Integer overflow in operator /");
+    assert_eq!(int(&format!("{} - 1 + 1;", max)), i128::MAX);
+}
+
+#[test]
+fn floats_follow_ieee() {
+    let val = Interpreter::new().run_str("0.0 / 0.0;").unwrap();
+    assert!(val.downcast::<Float>().unwrap().val().is_nan());
+    let val = Interpreter::new().run_str("1.0 / 0.0;").unwrap();
+    assert_eq!(val.downcast::<Float>().unwrap().val(), f64::INFINITY);
+}
+
+#[test]
+fn strict_nan_raises() {
+    let mut interp = Interpreter::new();
+    interp.env().set_strict_nan(true);
+    match interp.run_str("unique inf = 1.0 / 0.0; inf - inf;") {
+        Err(Error::Exception(e)) => assert_eq!(e.to_string(), "Operator - produced NaN"),
+        res => panic!("{:?}", res.map(|_| ())),
+    }
+    assert!(interp.run_str("1.0 / 0.0;").is_ok());
+    match interp.run_str("to_float(\"nan\");") {
+        Err(Error::Exception(e)) => assert_eq!(e.to_string(), "`to_float` produced NaN"),
+        res => panic!("{:?}", res.map(|_| ())),
+    }
+    let val = Interpreter::new().run_str("to_float(\"nan\");").unwrap();
+    assert!(val.downcast::<Float>().unwrap().val().is_nan());
+}
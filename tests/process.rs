@@ -0,0 +1,105 @@
+use std::fs;
+
+use assert_cmd::Command;
+use goose::interp::{Bit, CharArray, Int};
+use goose::{Error, Interpreter};
+
+fn text(src: &str) -> String {
+    Interpreter::new().run_str(src).unwrap().downcast::<CharArray>().unwrap().to_string()
+}
+
+fn exception(src: &str) -> String {
+    match Interpreter::new().run_str(src) {
+        Err(Error::Exception(e)) => e.to_string(),
+        Err(e) => panic!("{}", e),
+        Ok(_) => panic!("`{}` didn't raise", src),
+    }
+}
+
+/// Write `src` to a script only this test uses, returning its path
+fn script(name: &str, src: &str) -> String {
+    let path = std::env::temp_dir().join(format!("goose-process-{}-{}", std::process::id(), name));
+    fs::write(&path, src).unwrap();
+    path.display().to_string()
+}
+
+#[test]
+fn script_arguments() {
+    let path = script("args.hnk", "write(console, args.len, \" \", args#1);");
+    Command::cargo_bin(env!("CARGO_PKG_NAME"))
+        .unwrap()
+        .args([&path, "--", "a", "honk honk", "--strict-nan"])
+        .assert()
+        .success()
+        .stdout("3 honk honk");
+
+    let mut interp = Interpreter::new();
+    interp.set_args(&["x", "y"]);
+    let val = interp.run_str("args#0 + args#1;").unwrap();
+    assert_eq!(val.downcast::<CharArray>().unwrap().to_string(), "xy");
+}
+
+#[test]
+fn exit_status() {
+    let honk = std::env::temp_dir().join(format!("goose-process-{}-honk", std::process::id()));
+    let path = script(
+        "exit.hnk",
+        "
+            def stop: null (status: int) -> |1b| [
+                sync {
+                    write(honk, \"stopping\");
+                    exit(status);
+                }
+            ]
+            stop(to_int(args#0));
+            write(console, \"unreachable\");
+        ",
+    );
+    Command::cargo_bin(env!("CARGO_PKG_NAME"))
+        .unwrap()
+        .args([&path, "--", "42"])
+        .env("GOOSE_HONK", &honk)
+        .assert()
+        .code(42)
+        .stdout("");
+    // Exiting still writes out buffered honk output
+    assert_eq!(fs::read_to_string(&honk).unwrap(), "stopping");
+}
+
+#[test]
+fn exit_while_embedded() {
+    let mut interp = Interpreter::new();
+    assert!(matches!(interp.run_str("unique a = 1; exit(0);"), Err(Error::Exit(0))));
+    assert_eq!(**interp.run_str("a + 1;").unwrap().downcast::<Int>().unwrap(), 2);
+
+    let src = "def f: int () -> |exit(3)| [ 1; ] f();";
+    assert!(matches!(Interpreter::new().run_str(src), Err(Error::Exit(3))));
+    let src = "
+        def stop: bit () -> |1b| [ sync { exit(4); 1b; } ]
+        def f: int () -> |stop()| [ 1; ]
+        f();
+    ";
+    assert!(matches!(Interpreter::new().run_str(src), Err(Error::Exit(4))));
+
+    assert_eq!(exception("exit(256);"), "Exit status 256 is outside 0 to 255");
+    assert_eq!(exception("exit(-1);"), "Exit status -1 is outside 0 to 255");
+}
+
+#[test]
+fn environment_variables() {
+    let var = format!("GOOSE_TEST_{}", std::process::id());
+    let src = format!("set_env({:?}, \"honk\"); get_env({:?});", var, var);
+    assert_eq!(text(&src), "honk");
+
+    let missing = format!("{}_MISSING", var);
+    let val = Interpreter::new().run_str(&format!("has_env({:?});", missing)).unwrap();
+    assert!(!val.downcast::<Bit>().unwrap().val());
+    assert_eq!(
+        exception(&format!("get_env({:?});", missing)),
+        format!("Environment variable `{}` is not set", missing)
+    );
+    assert_eq!(
+        exception("set_env(\"A=B\", \"x\");"),
+        "Environment variable \"A=B\" can't be set to that value"
+    );
+}
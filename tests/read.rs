@@ -0,0 +1,121 @@
+use std::fs;
+use std::path::PathBuf;
+
+use goose::interp::CharArray;
+use goose::{Error, Interpreter};
+
+fn text(src: &str) -> String {
+    Interpreter::new().run_str(src).unwrap().downcast::<CharArray>().unwrap().to_string()
+}
+
+fn exception(src: &str) -> String {
+    match Interpreter::new().run_str(src) {
+        Err(Error::Exception(e)) => e.to_string(),
+        Err(e) => panic!("{}", e),
+        Ok(_) => panic!("`{}` didn't raise", src),
+    }
+}
+
+fn check(src: &str) -> Vec<String> {
+    let mut interp = Interpreter::new();
+    let id = interp.add_source("check.hnk", src);
+    let file = interp.parse(id).unwrap();
+    match interp.check(&file) {
+        Ok(()) => Vec::new(),
+        Err(Error::Check(diags)) => diags.iter().map(|d| d.message().to_string()).collect(),
+        Err(e) => panic!("{}", e),
+    }
+}
+
+/// Write `contents` to a file only this test uses, returning its path
+fn scratch(name: &str, contents: &str) -> PathBuf {
+    let path = std::env::temp_dir().join(format!("goose-read-{}-{}", std::process::id(), name));
+    fs::write(&path, contents).unwrap();
+    path
+}
+
+/// Run `src` as a script with `stdin` as its input, returning what it wrote to stdout
+fn run_with_input(name: &str, src: &str, stdin: &str) -> String {
+    let script = scratch(name, src);
+    let out = assert_cmd::Command::cargo_bin(env!("CARGO_PKG_NAME"))
+        .unwrap()
+        .arg(&script)
+        .write_stdin(stdin)
+        .unwrap();
+    fs::remove_file(script).unwrap();
+    String::from_utf8(out.stdout).unwrap()
+}
+
+#[test]
+fn lines_until_end_of_input() {
+    let src = r#"
+        def echo: chararray () -> |line == ""| [
+            once {
+                carryover line = "-";
+            }
+            line = read(console);
+            write(console, "> ", line);
+        ]
+        echo();
+    "#;
+    assert_eq!(run_with_input("echo.hnk", src, "honk\n\nhonk"), "> honk\n> \n> honk");
+}
+
+#[test]
+fn counts_and_everything() {
+    let src = r#"
+        write(console, read(console, 2), "|");
+        write(console, read(console, line), "|");
+        write(console, read(console, all), "|");
+        write(console, read(console, all), "|");
+    "#;
+    assert_eq!(run_with_input("count.hnk", src, "héllo\nwor\nld\n"), "hé|llo\n|wor\nld\n||");
+}
+
+#[test]
+fn from_an_fd() {
+    let src = r#"
+        write(console, read(0, 3), "|", read(console), "|", read(0, all));
+    "#;
+    assert_eq!(run_with_input("fd.hnk", src, "abcde\nf"), "abc|de\n|f");
+}
+
+#[test]
+fn from_a_file() {
+    let path = scratch("file.txt", "first\nsecond\n");
+    let path = path.display();
+    // A path is opened again for each read, so every read starts at the beginning
+    assert_eq!(text(&format!("read({:?});", path)), "first\n");
+    assert_eq!(text(&format!("read({:?}, 3);", path)), "fir");
+    assert_eq!(text(&format!("read({:?}, all);", path)), "first\nsecond\n");
+    assert_eq!(text(&format!("read_io({:?}, \"line\");", path)), "first\n");
+}
+
+#[test]
+fn invalid_reads() {
+    assert_eq!(
+        exception("read(console, -1);"),
+        "Expected a count, `line` or `all` to read, got -1"
+    );
+    assert_eq!(
+        exception("read_console(\"word\");"),
+        "Expected a count, `line` or `all` to read, got \"word\""
+    );
+    assert_eq!(exception("read(1.5);"), "Expected type `file`, got type `float`");
+    assert_eq!(exception("read(\"/nonexistent/goose\");"), "IO operation failed");
+}
+
+#[test]
+fn checked_as_chararray() {
+    let src = r#"
+        def count: int () -> |1b| [ read(console); ]
+        read(console, "two");
+    "#;
+    assert_eq!(
+        check(src),
+        [
+            "Expected type `int`, got type `chararray`",
+            "Expected type `int`, got type `chararray`",
+        ]
+    );
+}
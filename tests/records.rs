@@ -0,0 +1,140 @@
+use goose::interp::{Bit, CharArray, Int};
+use goose::{Error, Interpreter};
+
+const POINT: &str = "type Point = { x: int, y: int };\n";
+
+fn run(src: &str) -> Result<goose::interp::Value<'static>, Error> {
+    Interpreter::new().run_str(&format!("{}{}", POINT, src))
+}
+
+fn bit(src: &str) -> bool {
+    run(src).unwrap().downcast::<Bit>().unwrap().val()
+}
+
+fn int(src: &str) -> i128 {
+    run(src).unwrap().downcast::<Int>().unwrap().val()
+}
+
+fn exception(src: &str) -> String {
+    match run(src) {
+        Err(Error::Exception(e)) => e.to_string(),
+        Err(e) => panic!("{}", e),
+        Ok(_) => panic!("`{}` didn't raise", src),
+    }
+}
+
+fn check(src: &str) -> Vec<String> {
+    let mut interp = Interpreter::new();
+    let id = interp.add_source("check.hnk", &format!("{}{}", POINT, src));
+    let file = interp.parse(id).unwrap();
+    match interp.check(&file) {
+        Ok(()) => Vec::new(),
+        Err(Error::Check(diags)) => diags.iter().map(|d| d.message().to_string()).collect(),
+        Err(e) => panic!("{}", e),
+    }
+}
+
+#[test]
+fn construct_and_read() {
+    assert_eq!(int("unique p = Point { x: 1, y: 2 }; p.x + p.y;"), 3);
+    // Fields can be given in any order
+    assert_eq!(int("Point { y: 2, x: 1 }.x;"), 1);
+}
+
+#[test]
+fn update_fields() {
+    assert_eq!(int("unique p = Point { x: 1, y: 2 }; p.x = 10; p.x;"), 10);
+    assert_eq!(int("unique p = Point { x: 1, y: 2 }; p.y += 5; p.y;"), 7);
+    // Records are values, so copies aren't affected
+    assert_eq!(int("unique p = Point { x: 1, y: 2 }; unique q = p; p.x = 10; q.x;"), 1);
+    let nested = "
+        type Line = { from: Point, to: Point };
+        unique l = Line { from: Point { x: 0, y: 0 }, to: Point { x: 1, y: 1 } };
+        l.to.x = 5;
+        l.to.x + l.to.y;
+    ";
+    assert_eq!(int(nested), 6);
+}
+
+#[test]
+fn fields_keep_their_declared_type() {
+    let bag = "type Bag = { items: [int] }; unique b = Bag { items: [] };";
+    assert_eq!(int(&format!("{} b.items = [1, 2]; b.items = []; b.items = [3]; b.items.len;", bag)), 1);
+    assert_eq!(
+        exception(&format!("{} b.items = [\"a\"];", bag)),
+        "Expected type `[int]`, got type `[chararray]`"
+    );
+    assert!(check(&format!("{} b.items = [1, 2];", bag)).is_empty());
+}
+
+#[test]
+fn structural_equality() {
+    assert!(bit("Point { x: 1, y: 2 } == Point { y: 2, x: 1 };"));
+    assert!(bit("Point { x: 1, y: 2 } != Point { x: 1, y: 3 };"));
+    assert!(bit("[Point { x: 1, y: 2 }] == [Point { x: 1, y: 2 }];"));
+}
+
+#[test]
+fn nominal_identity() {
+    let src = "type Other = { x: int, y: int }; Point { x: 1, y: 2 } == Other { x: 1, y: 2 };";
+    assert!(exception(src).starts_with("Attempted to invoke binary operator =="));
+    let src = "
+        type Other = { x: int, y: int };
+        def get_x: int (p: Point) -> |1b| [ p.x; ]
+        get_x(Other { x: 1, y: 2 });
+    ";
+    assert_eq!(exception(src), "Expected type `Point`, got type `Other`");
+    assert_eq!(int("def get_x: int (p: Point) -> |1b| [ p.x; ] get_x(Point { x: 4, y: 2 });"), 4);
+}
+
+#[test]
+fn redeclaring_makes_a_new_type() {
+    let src = "
+        type P = { x: int };
+        unique a = P { x: 1 };
+        type P = { x: int };
+        a == P { x: 1 };
+    ";
+    assert!(exception(src).starts_with("Attempted to invoke binary operator =="));
+    assert!(bit("type P = { x: int }; unique a = P { x: 1 }; a == P { x: 1 };"));
+}
+
+#[test]
+fn write_output() {
+    let val = run("to_chararray(Point { x: 1, y: -2 });").unwrap();
+    assert_eq!(val.downcast::<CharArray>().unwrap().to_string(), "Point { x: 1, y: -2 }");
+}
+
+#[test]
+fn invalid_records_raise() {
+    assert_eq!(exception("Point { x: 1 };"), "Type `Point` is missing field `y`");
+    assert_eq!(exception("Point { x: 1, y: 2, z: 3 };"), "Type `Point` has no field `z`");
+    assert_eq!(exception("Point { x: 1, y: 'a' };"), "Expected type `int`, got type `char`");
+    assert_eq!(exception("int { x: 1 };"), "Type `int` is not a record");
+    assert_eq!(exception("unique p = Point { x: 1, y: 2 }; p.z = 1;"), "Type `Point` has no field `z`");
+    assert_eq!(exception("unique p = Point { x: 1, y: 2 }; p.x = 1.0;"), "Expected type `int`, got type `float`");
+    assert_eq!(exception("unique a = [1]; a.len = 2;"), "Field `len` of type `[int]` can't be assigned");
+}
+
+#[test]
+fn repeated_fields_dont_parse() {
+    let mut interp = Interpreter::new();
+    let id = interp.add_source("dup.hnk", "type P = { x: int, x: int };");
+    assert!(interp.parse(id).is_err());
+    let id = interp.add_source("dup.hnk", "unique p = Point { x: 1, x: 2 };");
+    assert!(interp.parse(id).is_err());
+}
+
+#[test]
+fn records_are_checked() {
+    assert_eq!(
+        check("unique p = Point { x: 1, y: 'a' }; p.z; p.x = 1.0; Point { x: 1 };"),
+        [
+            "Expected type `int`, got type `char`",
+            "Type `Point` has no field `z`",
+            "Expected type `int`, got type `float`",
+            "Type `Point` is missing field `y`",
+        ],
+    );
+    assert!(check("unique p = Point { x: 1, y: 2 }; p.x += 1; p.x * 2;").is_empty());
+}
@@ -0,0 +1,36 @@
+use assert_cmd::Command;
+
+fn repl(input: &str) -> String {
+    let out = Command::cargo_bin(env!("CARGO_PKG_NAME"))
+        .unwrap()
+        .arg("repl")
+        .write_stdin(input)
+        .unwrap();
+    String::from_utf8(out.stdout).unwrap()
+}
+
+#[test]
+fn echoes_expressions() {
+    assert_eq!(repl("unique a = 1;\na + 1;\n"), "2\n");
+}
+
+#[test]
+fn continues_incomplete_input() {
+    let input = "def f: int (x: int) -> |1b| [\n    x;\n]\n$$$\n    comment\n$$$\nf(3)\n";
+    assert_eq!(repl(input), "3\n");
+}
+
+#[test]
+fn comment_markers_in_strings_and_line_comments() {
+    assert_eq!(repl("\"$$$\".len;\n"), "3\n");
+    assert_eq!(repl("1; $ not a block $$$\n2;\n"), "1\n2\n");
+    assert_eq!(repl("$$$ one line $$$\n4;\n"), "4\n");
+    assert_eq!(repl("$$$ still open\n$$$\n5;\n"), "5\n");
+}
+
+#[test]
+fn survives_exceptions() {
+    let out = repl("b;\n1;\n");
+    assert!(out.starts_with("Exception:"));
+    assert!(out.ends_with("1\n"));
+}
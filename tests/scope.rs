@@ -0,0 +1,66 @@
+use goose::interp::Int;
+use goose::{Error, Interpreter};
+
+fn int(src: &str) -> i128 {
+    Interpreter::new().run_str(src).unwrap().downcast::<Int>().unwrap().val()
+}
+
+fn exception(src: &str) -> String {
+    match Interpreter::new().run_str(src) {
+        Err(Error::Exception(e)) => e.to_string(),
+        Err(e) => panic!("{}", e),
+        Ok(_) => panic!("`{}` didn't raise", src),
+    }
+}
+
+#[test]
+fn callees_cant_see_caller_locals() {
+    let src = "
+        def peek: int () -> |1b| [ secret; ]
+        def caller: int () -> |out == out| [ unique secret = 1; unique out = peek(); ]
+        caller();
+    ";
+    assert_eq!(exception(src), "Attempted to access invalid identifier secret");
+}
+
+#[test]
+fn closures_see_their_defining_scope() {
+    let src = "
+        def make: fn: int () (n: int) -> |1b| [ fn: int () -> |1b| [ n * 2; ]; ]
+        def call: int (f: fn: int ()) -> |out == out| [ unique n = 100; unique out = f(); ]
+        call(make(21));
+    ";
+    assert_eq!(int(src), 42);
+}
+
+#[test]
+fn if_pattern_from_another_function() {
+    let src = "
+        def if: bit (cond: bit, block: fn: int ()) -> |1b| [ cond && block() == 0; ]
+        def count: int (n: int) -> |out == out| [
+            unique total = 0;
+            if(n > 1, fn: int () -> |done == done| [
+                total += n;
+                unique done = 0;
+            ]);
+            unique out = total;
+        ]
+        count(5);
+    ";
+    assert_eq!(int(src), 5);
+}
+
+#[test]
+fn captured_variables_persist_between_calls() {
+    let src = "
+        def counter: fn: int () () -> |out == out| [
+            unique count = 0;
+            unique out = fn: int () -> |1b| [ count += 1; ];
+        ]
+        unique next = counter();
+        next();
+        next();
+        next();
+    ";
+    assert_eq!(int(src), 3);
+}
@@ -0,0 +1,119 @@
+use goose::interp::{Bit, CharArray, Float, Int};
+use goose::{Error, Interpreter};
+
+const SHAPE: &str = "type Shape = Circle(float) | Square(float) | Empty;\n";
+
+fn run(src: &str) -> Result<goose::interp::Value<'static>, Error> {
+    Interpreter::new().run_str(&format!("{}{}", SHAPE, src))
+}
+
+fn bit(src: &str) -> bool {
+    run(src).unwrap().downcast::<Bit>().unwrap().val()
+}
+
+fn text(src: &str) -> String {
+    run(src).unwrap().downcast::<CharArray>().unwrap().to_string()
+}
+
+fn exception(src: &str) -> String {
+    match run(src) {
+        Err(Error::Exception(e)) => e.to_string(),
+        Err(e) => panic!("{}", e),
+        Ok(_) => panic!("`{}` didn't raise", src),
+    }
+}
+
+fn check(src: &str) -> Vec<String> {
+    let mut interp = Interpreter::new();
+    let id = interp.add_source("check.hnk", &format!("{}{}", SHAPE, src));
+    let file = interp.parse(id).unwrap();
+    match interp.check(&file) {
+        Ok(()) => Vec::new(),
+        Err(Error::Check(diags)) => diags.iter().map(|d| d.message().to_string()).collect(),
+        Err(e) => panic!("{}", e),
+    }
+}
+
+#[test]
+fn testing_variants() {
+    assert!(bit("is(Circle(1.0), Circle);"));
+    assert!(!bit("is(Circle(1.0), Square);"));
+    assert!(bit("is(Empty(), Empty);"));
+}
+
+#[test]
+fn extracting_payloads() {
+    let val = run("payload(Square(2.5), Square);").unwrap();
+    assert_eq!(val.downcast::<Float>().unwrap().val(), 2.5);
+    assert_eq!(
+        exception("payload(Circle(1.0), Square);"),
+        "Expected variant `Square`, got variant `Circle`",
+    );
+    assert_eq!(exception("payload(Circle(1.0), len);"), "Function `len` is not a variant constructor");
+}
+
+#[test]
+fn variants_in_limits() {
+    let src = "
+        def area: float (s: Shape) -> |is(s, Square)| [
+            payload(s, Square) * payload(s, Square);
+        ]
+        area(Square(3.0));
+    ";
+    let val = run(src).unwrap();
+    assert_eq!(val.downcast::<Float>().unwrap().val(), 9.0);
+
+    let src = "
+        def settle: Shape (s: Shape) -> |is(s, Empty)| [
+            s = Empty();
+        ]
+        is(settle(Circle(1.0)), Empty);
+    ";
+    assert!(bit(src));
+}
+
+#[test]
+fn equality() {
+    assert!(bit("Circle(1.0) == Circle(1.0);"));
+    assert!(bit("Circle(1.0) != Circle(2.0);"));
+    assert!(bit("Circle(1.0) != Square(1.0);"));
+    assert!(bit("Empty() == Empty();"));
+    let src = "type Other = Circle(float) | Nothing; unique o = Circle(1.0); is(o, Nothing);";
+    assert!(!bit(src));
+}
+
+#[test]
+fn nominal_identity() {
+    let src = "
+        type Answer = Yes | No;
+        unique s = Empty();
+        is(s, Yes);
+    ";
+    assert_eq!(exception(src), "Expected type `Answer`, got type `Shape`");
+    assert!(exception("type Answer = Yes | No; Yes() == Empty();").starts_with("Attempted to invoke binary operator =="));
+}
+
+#[test]
+fn write_output() {
+    assert_eq!(text("to_chararray(Circle(1.5));"), "Circle(1.5)");
+    assert_eq!(text("to_chararray(Empty());"), "Empty");
+    assert_eq!(text("to_chararray([Empty(), Square(2.0)]);"), "[Empty, Square(2)]");
+}
+
+#[test]
+fn constructors_are_checked() {
+    assert_eq!(exception("Circle('a');"), "Expected type `float`, got type `char`");
+    assert_eq!(exception("Empty(1.0);"), "Function `Empty` expects 0 arguments, got 1");
+    assert_eq!(
+        check("Circle('a'); Empty(1.0); is(Circle(1.0), Circle);"),
+        ["Expected type `float`, got type `char`", "Function `Empty` expects 0 arguments, got 1"],
+    );
+}
+
+#[test]
+fn plain_names_are_aliases() {
+    let val = Interpreter::new().run_str("type Num = int; def f: Num (n: Num) -> |1b| [ n; ] f(3);").unwrap();
+    assert_eq!(val.downcast::<Int>().unwrap().val(), 3);
+    let val = Interpreter::new().run_str("type Wrap = Box(int); payload(Box(3), Box);").unwrap();
+    assert_eq!(val.downcast::<Int>().unwrap().val(), 3);
+}
@@ -0,0 +1,78 @@
+use goose::interp::{Bit, Int};
+use goose::{Error, Interpreter};
+
+fn run_int(src: &str) -> i128 {
+    let val = Interpreter::new().run_str(src).unwrap();
+    **val.downcast::<Int>().unwrap()
+}
+
+#[test]
+fn limit_loops_until_reached() {
+    let src = "
+        def count: int (n: int) -> |i == n| [
+            once {
+                carryover i = 0;
+            }
+            i += 1;
+        ]
+        count(5);
+    ";
+    assert_eq!(run_int(src), 5);
+}
+
+#[test]
+fn limit_errors_are_not_reached() {
+    let src = "
+        def foo: int (i: int) -> |baz != bar| [
+            once {
+                carryover baz = i;
+            }
+            unique bar = i;
+            sync {
+                bar += 1;
+                baz += 1;
+            }
+            bar += 1;
+            baz += 1;
+        ]
+        foo(2);
+    ";
+    assert_eq!(run_int(src), 4);
+}
+
+#[test]
+fn nested_calls_keep_their_own_iteration() {
+    let src = "
+        def inner: int () -> |1b| [
+            0;
+        ]
+        def outer: int () -> |n == 3| [
+            inner();
+            carryover n = 0;
+            n += 1;
+        ]
+        outer();
+    ";
+    assert_eq!(run_int(src), 3);
+}
+
+#[test]
+fn return_type_is_checked() {
+    let src = "
+        def wrong: int () -> |1b| [
+            1b;
+        ]
+        wrong();
+    ";
+    assert!(matches!(Interpreter::new().run_str(src), Err(Error::Exception(_))));
+}
+
+#[test]
+fn closures_are_values() {
+    let src = "
+        unique f = fn: bit () -> |1b| [ 1b; ];
+        f();
+    ";
+    let val = Interpreter::new().run_str(src).unwrap();
+    assert!(val.downcast::<Bit>().unwrap().val());
+}